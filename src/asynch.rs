@@ -0,0 +1,359 @@
+//! Asynchronous counterparts of Interface and Controller, for buses whose
+//! transfers (typically DMA) can be awaited while an executor services
+//! other tasks.
+
+use crate::{
+	BitsPerPixel, Color, CtrlDisplay, DisplayIdentification, DisplayPowerMode, DisplayStatus,
+	Error, ImageFormat, MADCtl, MemoryAccessControl, NoResetPin, PixelFormat,
+	ResetPin, SelfDiagnosticResult, SignalMode, TearingEffect, READ_CHUNK_PIXELS,
+};
+
+/// Asynchronous version of the Interface trait, with the same contract for
+/// each method.
+#[allow(async_fn_in_trait)]
+pub trait AsyncInterface {
+	type Error;
+
+	async fn write_parameters(&mut self, command: u8, data: &[u8]) -> Result<(), Self::Error>;
+	async fn write_memory<I>(&mut self, iterable: I) -> Result<(), Self::Error> where I: IntoIterator<Item=u32>;
+	async fn write_repeated(&mut self, pixel: u32, count: u32) -> Result<(), Self::Error> {
+		self.write_memory((0..count).map(|_| pixel)).await
+	}
+	async fn read_parameters(&mut self, command: u8, data: &mut [u8]) -> Result<(), Self::Error>;
+	async fn read_memory(&mut self, data: &mut [u32]) -> Result<(), Self::Error>;
+	async fn panel_reset(&mut self) -> Result<(), Self::Error> {
+		Ok(())
+	}
+}
+
+/// Asynchronous version of Controller, implementing the same command set
+/// over an AsyncInterface.
+#[derive(Copy, Clone)]
+pub struct AsyncController<T, RST = NoResetPin>
+	where T: AsyncInterface
+{
+	pub(crate) iface: T,
+	pub(crate) reset: RST,
+	pub(crate) madctl: MemoryAccessControl,
+	pub(crate) format: BitsPerPixel,
+}
+
+impl<T: AsyncInterface> AsyncController<T> {
+	pub fn new(iface: T) -> AsyncController<T> {
+		AsyncController {
+			iface,
+			reset: NoResetPin,
+			madctl: MemoryAccessControl::default(),
+			format: BitsPerPixel::Eighteen,
+		}
+	}
+}
+
+impl<T: AsyncInterface, RST: ResetPin> AsyncController<T, RST> {
+	/// Construct an AsyncController that drives the RESX pin with `reset`.
+	pub fn with_reset_pin(iface: T, reset: RST) -> AsyncController<T, RST> {
+		AsyncController {
+			iface,
+			reset,
+			madctl: MemoryAccessControl::default(),
+			format: BitsPerPixel::Eighteen,
+		}
+	}
+
+	/// Consume the AsyncController, returning the AsyncInterface and reset
+	/// pin.
+	pub fn release(self) -> (T, RST) {
+		(self.iface, self.reset)
+	}
+
+	pub(crate) async fn write_command(&mut self, command: u8) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_parameters(command, &[]).await
+	}
+
+	pub(crate) async fn write_parameters(&mut self, command: u8, parameters: &[u8]) -> Result<(), Error<T::Error, RST::Error>> {
+		self.iface.write_parameters(command, parameters).await?;
+		Ok(())
+	}
+
+	pub(crate) async fn read_parameters(&mut self, command: u8, parameters: &mut [u8]) -> Result<(), Error<T::Error, RST::Error>> {
+		self.iface.read_parameters(command, parameters).await?;
+		Ok(())
+	}
+
+	pub async fn nop(&mut self) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_command(0x00).await
+	}
+
+	pub async fn software_reset(&mut self) -> Result<(), Error<T::Error, RST::Error>> {
+		self.madctl = MemoryAccessControl::default();
+		self.format = BitsPerPixel::Eighteen;
+		self.write_command(0x01).await
+	}
+
+	pub async fn read_display_identification(&mut self) -> Result<DisplayIdentification, Error<T::Error, RST::Error>> {
+		let mut result = DisplayIdentification::default();
+		self.read_parameters(0x04, &mut result.raw).await?;
+		Ok(result)
+	}
+
+	pub async fn read_display_status(&mut self) -> Result<DisplayStatus, Error<T::Error, RST::Error>> {
+		let mut result = DisplayStatus::default();
+		self.read_parameters(0x09, &mut result.raw).await?;
+		Ok(result)
+	}
+
+	pub async fn read_display_power_mode(&mut self) -> Result<DisplayPowerMode, Error<T::Error, RST::Error>> {
+		let mut result = DisplayPowerMode::default();
+		self.read_parameters(0x0a, &mut result.raw).await?;
+		Ok(result)
+	}
+
+	pub async fn read_display_madctl(&mut self) -> Result<MADCtl, Error<T::Error, RST::Error>> {
+		let mut result = MADCtl::default();
+		self.read_parameters(0x0b, &mut result.raw).await?;
+		self.madctl = result;
+		Ok(result)
+	}
+
+	pub async fn read_pixel_format(&mut self) -> Result<PixelFormat, Error<T::Error, RST::Error>> {
+		let mut result = PixelFormat::default();
+		self.read_parameters(0x0c, &mut result.raw).await?;
+		if let Some(dbi) = result.dbi() {
+			self.format = dbi;
+		}
+		Ok(result)
+	}
+
+	pub async fn read_image_format(&mut self) -> Result<ImageFormat, Error<T::Error, RST::Error>> {
+		let mut result = ImageFormat::default();
+		self.read_parameters(0x0d, &mut result.raw).await?;
+		Ok(result)
+	}
+
+	pub async fn read_signal_mode(&mut self) -> Result<SignalMode, Error<T::Error, RST::Error>> {
+		let mut result = SignalMode::default();
+		self.read_parameters(0x0e, &mut result.raw).await?;
+		Ok(result)
+	}
+
+	pub async fn read_self_diagnostic_result(&mut self) -> Result<SelfDiagnosticResult, Error<T::Error, RST::Error>> {
+		let mut result = SelfDiagnosticResult::default();
+		self.read_parameters(0x0f, &mut result.raw).await?;
+		Ok(result)
+	}
+
+	pub async fn enter_sleep_mode(&mut self) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_command(0x10).await
+	}
+
+	pub async fn sleep_out(&mut self) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_command(0x11).await
+	}
+
+	pub async fn partial_mode_on(&mut self) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_command(0x12).await
+	}
+
+	pub async fn normal_display_mode_on(&mut self) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_command(0x13).await
+	}
+
+	pub async fn display_inversion(&mut self, on: bool) -> Result<(), Error<T::Error, RST::Error>> {
+		let command = match on {
+			false => 0x20,
+			true  => 0x21,
+		};
+		self.write_command(command).await
+	}
+
+	pub async fn gamma_set(&mut self, gc: u8) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_parameters(0x26, &[gc]).await
+	}
+
+	pub async fn display(&mut self, on: bool) -> Result<(), Error<T::Error, RST::Error>> {
+		let command = match on {
+			false => 0x28,
+			true  => 0x29,
+		};
+		self.write_command(command).await
+	}
+
+	pub async fn column_address_set(&mut self, sc: u16, ec: u16) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_parameters(0x2a, &[
+			(sc >> 8) as u8, (sc & 0xff) as u8,
+			(ec >> 8) as u8, (ec & 0xff) as u8,
+		]).await
+	}
+
+	pub async fn page_address_set(&mut self, sp: u16, ep: u16) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_parameters(0x2b, &[
+			(sp >> 8) as u8, (sp & 0xff) as u8,
+			(ep >> 8) as u8, (ep & 0xff) as u8,
+		]).await
+	}
+
+	pub async fn memory_write_start(&mut self) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_command(0x2c).await
+	}
+
+	pub async fn color_set(&mut self, data: &[u8; 128]) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_parameters(0x2d, data).await
+	}
+
+	pub async fn memory_read_start(&mut self) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_command(0x2e).await
+	}
+
+	pub async fn partial_area(&mut self, sr: u16, er: u16) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_parameters(0x30, &[
+			(sr >> 8) as u8, (sr & 0xff) as u8,
+			(er >> 8) as u8, (er & 0xff) as u8,
+		]).await
+	}
+
+	pub async fn vertical_scrolling_definition(&mut self, tfa: u16, vsa: u16, bfa: u16) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_parameters(0x33, &[
+			(tfa >> 8) as u8, (tfa & 0xff) as u8,
+			(vsa >> 8) as u8, (vsa & 0xff) as u8,
+			(bfa >> 8) as u8, (bfa & 0xff) as u8,
+		]).await
+	}
+
+	pub async fn tearing_effect(&mut self, mode: TearingEffect) -> Result<(), Error<T::Error, RST::Error>> {
+		match mode {
+			TearingEffect::VBlankOnly => self.write_parameters(0x35, &[0u8]).await,
+			TearingEffect::HAndVBlank => self.write_parameters(0x35, &[1u8]).await,
+			_                         => self.write_command(0x34).await,
+		}
+	}
+
+	pub async fn memory_access_control(&mut self, value: MemoryAccessControl) -> Result<(), Error<T::Error, RST::Error>> {
+		self.madctl = value;
+		self.write_parameters(0x36, &value.raw).await
+	}
+
+	pub async fn vertical_scrolling_start_address(&mut self, vsp: u16) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_parameters(0x37, &[
+			(vsp >> 8) as u8, (vsp & 0xff) as u8,
+		]).await
+	}
+
+	pub async fn idle_mode(&mut self, on: bool) -> Result<(), Error<T::Error, RST::Error>> {
+		let command = match on {
+			false => 0x38,
+			true  => 0x39,
+		};
+		self.write_command(command).await
+	}
+
+	pub async fn pixel_format_set(&mut self, value: PixelFormat) -> Result<(), Error<T::Error, RST::Error>> {
+		if let Some(dbi) = value.dbi() {
+			self.format = dbi;
+		}
+		self.write_parameters(0x3a, &value.raw).await
+	}
+
+	pub async fn write_memory_continue(&mut self) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_command(0x3c).await
+	}
+
+	/// Stream pixels following `memory_write_start` or
+	/// `write_memory_continue`, encoded for the current pixel format.
+	pub async fn write_memory<C, I>(&mut self, iterable: I) -> Result<(), Error<T::Error, RST::Error>>
+		where C: Color,
+		      I: IntoIterator<Item=C>
+	{
+		let format = self.format;
+		self.iface.write_memory(iterable.into_iter().map(move |c| c.to_raw(format))).await?;
+		Ok(())
+	}
+
+	pub async fn read_memory_continue(&mut self) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_command(0x3e).await
+	}
+
+	/// Read pixels following `memory_read_start` or
+	/// `read_memory_continue`.
+	pub async fn read_memory<C: Color>(&mut self, data: &mut [C]) -> Result<(), Error<T::Error, RST::Error>> {
+		let mut buffer = [0u32; READ_CHUNK_PIXELS];
+		for chunk in data.chunks_mut(READ_CHUNK_PIXELS) {
+			let raw = &mut buffer[..chunk.len()];
+			self.iface.read_memory(raw).await?;
+			for (pixel, &value) in chunk.iter_mut().zip(raw.iter()) {
+				*pixel = C::from_raw_read(value);
+			}
+		}
+		Ok(())
+	}
+	
+	pub async fn set_tear_scanline(&mut self, sts: u16) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_parameters(0x44, &[
+			(sts >> 8) as u8, (sts & 0xff) as u8,
+		]).await
+	}
+
+	pub async fn get_scanline(&mut self) -> Result<u16, Error<T::Error, RST::Error>> {
+		let mut result = [0u8; 2];
+		self.read_parameters(0x45, &mut result).await?;
+		Ok(((result[0] as u16) << 8) | result[1] as u16)
+	}
+
+	pub async fn write_display_brightness(&mut self, dbv: u8) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_parameters(0x51, &[dbv]).await
+	}
+
+	pub async fn read_display_brightness(&mut self) -> Result<u8, Error<T::Error, RST::Error>> {
+		let mut result = [0u8; 1];
+		self.read_parameters(0x52, &mut result).await?;
+		Ok(result[0])
+	}
+
+	pub async fn write_ctrl_display(&mut self, value: CtrlDisplay) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_parameters(0x53, &value.raw).await
+	}
+
+	pub async fn read_ctrl_display(&mut self) -> Result<CtrlDisplay, Error<T::Error, RST::Error>> {
+		let mut result = CtrlDisplay::default();
+		self.read_parameters(0x54, &mut result.raw).await?;
+		Ok(result)
+	}
+
+	pub async fn write_cabc(&mut self, c: u8) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_parameters(0x55, &[c]).await
+	}
+
+	pub async fn read_cabc(&mut self) -> Result<u8, Error<T::Error, RST::Error>> {
+		let mut result = [0u8; 1];
+		self.read_parameters(0x56, &mut result).await?;
+		Ok(result[0])
+	}
+
+	pub async fn write_cabc_minimum_brightness(&mut self, cmb: u8) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_parameters(0x5e, &[cmb]).await
+	}
+
+	pub async fn read_cabc_minimum_brightness(&mut self) -> Result<u8, Error<T::Error, RST::Error>> {
+		let mut result = [0u8; 1];
+		self.read_parameters(0x5f, &mut result).await?;
+		Ok(result[0])
+	}
+
+	pub async fn read_id1(&mut self) -> Result<u8, Error<T::Error, RST::Error>> {
+		let mut result = [0u8; 1];
+		self.read_parameters(0xda, &mut result).await?;
+		Ok(result[0])
+	}
+
+	pub async fn read_id2(&mut self) -> Result<u8, Error<T::Error, RST::Error>> {
+		let mut result = [0u8; 1];
+		self.read_parameters(0xdb, &mut result).await?;
+		Ok(result[0])
+	}
+
+	pub async fn read_id3(&mut self) -> Result<u8, Error<T::Error, RST::Error>> {
+		let mut result = [0u8; 1];
+		self.read_parameters(0xdc, &mut result).await?;
+		Ok(result[0])
+	}
+
+}
@@ -0,0 +1,216 @@
+//! Pixel color types, and their encoding for the COLMOD DBI formats.
+
+use crate::BitsPerPixel;
+
+/// A pixel color the Controller can write to and read from GRAM.
+///
+/// Colors are encoded for whichever DBI format the Controller last set,
+/// so a color type never needs to match the configured format.
+pub trait Color: Copy {
+	/// Encode right-aligned in `format`, as Interface::write_memory
+	/// expects: RGB565 in the low 16 bits, or RGB666 in the low 18 bits.
+	fn to_raw(self, format: BitsPerPixel) -> u32;
+
+	/// Decode a pixel read back from GRAM, as returned by
+	/// Interface::read_memory: 0x00RRGGBB with 6 significant bits per
+	/// channel, left-aligned in each byte.
+	fn from_raw_read(raw: u32) -> Self;
+}
+
+/// Widen a channel from `from` to 6 bits, replicating the high bits into
+/// the new low bits so full scale stays full scale.
+fn widen(value: u8, from: u8) -> u8 {
+	let shift = 6 - from;
+	(value << shift) | (value >> (from - shift))
+}
+
+/// The 6-bit channels of a pixel read back from GRAM.
+fn read_channels(raw: u32) -> (u8, u8, u8) {
+	(
+		(raw >> 18) as u8 & 0x3f,
+		(raw >> 10) as u8 & 0x3f,
+		(raw >> 2) as u8 & 0x3f,
+	)
+}
+
+fn rgb666_raw(r: u8, g: u8, b: u8) -> u32 {
+	((r as u32) << 12) | ((g as u32) << 6) | b as u32
+}
+
+/// 16-bit color: 5 bits red, 6 bits green, 5 bits blue.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Rgb565(u16);
+
+impl Rgb565 {
+	pub const BLACK: Rgb565 = Rgb565(0x0000);
+	pub const WHITE: Rgb565 = Rgb565(0xffff);
+
+	/// Build from channel values, each masked to its width.
+	pub const fn new(r: u8, g: u8, b: u8) -> Rgb565 {
+		Rgb565((((r & 0x1f) as u16) << 11) | (((g & 0x3f) as u16) << 5) | (b & 0x1f) as u16)
+	}
+
+	/// Build from 8-bit channels, discarding the low bits.
+	pub const fn from_rgb888(r: u8, g: u8, b: u8) -> Rgb565 {
+		Rgb565::new(r >> 3, g >> 2, b >> 3)
+	}
+
+	pub const fn from_bits(bits: u16) -> Rgb565 {
+		Rgb565(bits)
+	}
+
+	pub const fn bits(&self) -> u16 {
+		self.0
+	}
+
+	pub const fn r(&self) -> u8 {
+		(self.0 >> 11) as u8
+	}
+
+	pub const fn g(&self) -> u8 {
+		(self.0 >> 5) as u8 & 0x3f
+	}
+
+	pub const fn b(&self) -> u8 {
+		self.0 as u8 & 0x1f
+	}
+}
+
+impl Color for Rgb565 {
+	fn to_raw(self, format: BitsPerPixel) -> u32 {
+		match format {
+			BitsPerPixel::Sixteen  => self.0 as u32,
+			BitsPerPixel::Eighteen => Rgb666::from(self).to_raw(format),
+		}
+	}
+
+	fn from_raw_read(raw: u32) -> Rgb565 {
+		let (r, g, b) = read_channels(raw);
+		Rgb565::new(r >> 1, g, b >> 1)
+	}
+}
+
+/// 18-bit color: 6 bits each of red, green and blue.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Rgb666 {
+	r: u8,
+	g: u8,
+	b: u8,
+}
+
+impl Rgb666 {
+	pub const BLACK: Rgb666 = Rgb666::new(0x00, 0x00, 0x00);
+	pub const WHITE: Rgb666 = Rgb666::new(0x3f, 0x3f, 0x3f);
+
+	/// Build from channel values, each masked to 6 bits.
+	pub const fn new(r: u8, g: u8, b: u8) -> Rgb666 {
+		Rgb666 {
+			r: r & 0x3f,
+			g: g & 0x3f,
+			b: b & 0x3f,
+		}
+	}
+
+	/// Build from 8-bit channels, discarding the low bits.
+	pub const fn from_rgb888(r: u8, g: u8, b: u8) -> Rgb666 {
+		Rgb666::new(r >> 2, g >> 2, b >> 2)
+	}
+
+	pub const fn r(&self) -> u8 {
+		self.r
+	}
+
+	pub const fn g(&self) -> u8 {
+		self.g
+	}
+
+	pub const fn b(&self) -> u8 {
+		self.b
+	}
+
+	/// Widen to 8-bit channels, replicating the high bits so full scale
+	/// stays full scale.
+	pub const fn to_rgb888(&self) -> [u8; 3] {
+		[
+			(self.r << 2) | (self.r >> 4),
+			(self.g << 2) | (self.g >> 4),
+			(self.b << 2) | (self.b >> 4),
+		]
+	}
+}
+
+impl From<Rgb565> for Rgb666 {
+	/// Widen red and blue to 6 bits as the panel does for 16-bit pixels.
+	fn from(color: Rgb565) -> Rgb666 {
+		Rgb666::new(widen(color.r(), 5), color.g(), widen(color.b(), 5))
+	}
+}
+
+impl Color for Rgb666 {
+	fn to_raw(self, format: BitsPerPixel) -> u32 {
+		match format {
+			BitsPerPixel::Sixteen  => Rgb565::new(self.r >> 1, self.g, self.b >> 1).0 as u32,
+			BitsPerPixel::Eighteen => rgb666_raw(self.r, self.g, self.b),
+		}
+	}
+
+	fn from_raw_read(raw: u32) -> Rgb666 {
+		let (r, g, b) = read_channels(raw);
+		Rgb666::new(r, g, b)
+	}
+}
+
+/// 3-bit color: each channel fully on or off, the eight colors shown in
+/// idle mode.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Rgb111(u8);
+
+impl Rgb111 {
+	pub const BLACK: Rgb111 = Rgb111::new(false, false, false);
+	pub const BLUE: Rgb111 = Rgb111::new(false, false, true);
+	pub const GREEN: Rgb111 = Rgb111::new(false, true, false);
+	pub const CYAN: Rgb111 = Rgb111::new(false, true, true);
+	pub const RED: Rgb111 = Rgb111::new(true, false, false);
+	pub const MAGENTA: Rgb111 = Rgb111::new(true, false, true);
+	pub const YELLOW: Rgb111 = Rgb111::new(true, true, false);
+	pub const WHITE: Rgb111 = Rgb111::new(true, true, true);
+
+	pub const fn new(r: bool, g: bool, b: bool) -> Rgb111 {
+		Rgb111(((r as u8) << 2) | ((g as u8) << 1) | b as u8)
+	}
+
+	/// Build from 8-bit channels, keeping only the most significant bit.
+	pub const fn from_rgb888(r: u8, g: u8, b: u8) -> Rgb111 {
+		Rgb111::new(r >= 0x80, g >= 0x80, b >= 0x80)
+	}
+
+	pub const fn r(&self) -> bool {
+		self.0 & 0x04 != 0
+	}
+
+	pub const fn g(&self) -> bool {
+		self.0 & 0x02 != 0
+	}
+
+	pub const fn b(&self) -> bool {
+		self.0 & 0x01 != 0
+	}
+}
+
+impl From<Rgb111> for Rgb666 {
+	fn from(color: Rgb111) -> Rgb666 {
+		let full = |on: bool| if on { 0x3f } else { 0x00 };
+		Rgb666::new(full(color.r()), full(color.g()), full(color.b()))
+	}
+}
+
+impl Color for Rgb111 {
+	fn to_raw(self, format: BitsPerPixel) -> u32 {
+		Rgb666::from(self).to_raw(format)
+	}
+
+	fn from_raw_read(raw: u32) -> Rgb111 {
+		let (r, g, b) = read_channels(raw);
+		Rgb111::new(r & 0x20 != 0, g & 0x20 != 0, b & 0x20 != 0)
+	}
+}
@@ -0,0 +1,553 @@
+//! Command stream decoder: turns bytes captured from the bus, tagged with
+//! the D/CX level, back into the commands that produced them.
+
+use core::fmt;
+
+use crate::{
+	DisplayFunctionControl, EntryMode, FrameRateControl, GammaCurve,
+	MemoryAccessControl, PixelFormat, PowerControl1, PowerControl2,
+	TearingEffect, VcomControl1, VcomControl2,
+};
+
+/// Longest parameter list kept, that of Color Set (2Dh).
+const MAX_PARAMETERS: usize = 128;
+
+/// Parameter bytes of a command that could not be interpreted.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub struct Parameters {
+	raw: [u8; MAX_PARAMETERS],
+	len: usize,
+}
+
+impl Parameters {
+	pub fn as_slice(&self) -> &[u8] {
+		&self.raw[..self.len]
+	}
+
+	/// Append `byte`, returning false if there is no room.
+	fn push(&mut self, byte: u8) -> bool {
+		match self.raw.get_mut(self.len) {
+			Some(slot) => {
+				*slot = byte;
+				self.len += 1;
+				true
+			},
+			None => false,
+		}
+	}
+}
+
+impl Default for Parameters {
+	fn default() -> Parameters {
+		Parameters {
+			raw: [0; MAX_PARAMETERS],
+			len: 0,
+		}
+	}
+}
+
+impl fmt::Debug for Parameters {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{:02x?}", self.as_slice())
+	}
+}
+
+/// Number of parameters a command takes.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub(crate) enum Length {
+	Fixed(usize),
+	/// Either of two counts, the longer list ending early.
+	Range(usize, usize),
+	/// Followed by pixel data, or by data read from the panel.
+	Stream,
+	/// Not a known command.
+	Unknown,
+}
+
+pub(crate) fn length(command: u8) -> Length {
+	match command {
+		0x00 | 0x01 | 0x10..=0x13 | 0x20 | 0x21 | 0x28 | 0x29 | 0x34 | 0x38 | 0x39 => Length::Fixed(0),
+		0x04 | 0x09..=0x0f | 0x2c | 0x2e | 0x3c | 0x3e | 0x45 | 0x52 | 0x54 | 0x56 | 0x5f | 0xd3 | 0xda..=0xdc => Length::Stream,
+		0x26 | 0x35 | 0x36 | 0x3a | 0x51 | 0x53 | 0x55 | 0x5e | 0xb0 | 0xb4 | 0xb7 | 0xc0 | 0xc1 | 0xc7 | 0xf2 | 0xf7 => Length::Fixed(1),
+		0x37 | 0x44 | 0xb1..=0xb3 | 0xc5 | 0xea => Length::Fixed(2),
+		0xcf | 0xe8 | 0xf6 => Length::Fixed(3),
+		0x2a | 0x2b | 0x30 | 0xb5 | 0xed => Length::Fixed(4),
+		0xb6 => Length::Range(3, 4),
+		0xcb => Length::Fixed(5),
+		0x33 => Length::Fixed(6),
+		0xe0 | 0xe1 => Length::Fixed(15),
+		0xe2 => Length::Fixed(16),
+		0xe3 => Length::Fixed(64),
+		0x2d => Length::Fixed(128),
+		_ => Length::Unknown,
+	}
+}
+
+/// A decoded command, named after the Controller method that sends it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Command {
+	Nop,
+	SoftwareReset,
+	ReadDisplayIdentification,
+	ReadDisplayStatus,
+	ReadDisplayPowerMode,
+	ReadDisplayMadctl,
+	ReadPixelFormat,
+	ReadImageFormat,
+	ReadSignalMode,
+	ReadSelfDiagnosticResult,
+	EnterSleepMode,
+	SleepOut,
+	PartialModeOn,
+	NormalDisplayModeOn,
+	DisplayInversion(bool),
+	GammaSet(u8),
+	Display(bool),
+	ColumnAddressSet { sc: u16, ec: u16 },
+	PageAddressSet { sp: u16, ep: u16 },
+	MemoryWriteStart,
+	ColorSet([u8; 128]),
+	MemoryReadStart,
+	PartialArea { sr: u16, er: u16 },
+	VerticalScrollingDefinition { tfa: u16, vsa: u16, bfa: u16 },
+	TearingEffect(TearingEffect),
+	MemoryAccessControl(MemoryAccessControl),
+	VerticalScrollingStartAddress { vsp: u16 },
+	IdleMode(bool),
+	PixelFormatSet(PixelFormat),
+	WriteMemoryContinue,
+	ReadMemoryContinue,
+	SetTearScanline { sts: u16 },
+	GetScanline,
+	WriteDisplayBrightness(u8),
+	ReadDisplayBrightness,
+	WriteCtrlDisplay(u8),
+	ReadCtrlDisplay,
+	WriteCabc(u8),
+	ReadCabc,
+	WriteCabcMinimumBrightness(u8),
+	ReadCabcMinimumBrightness,
+	ReadId1,
+	ReadId2,
+	ReadId3,
+	ReadId4,
+	RgbInterfaceSignalControl(u8),
+	FrameRateControlNormal(FrameRateControl),
+	FrameRateControlIdle(FrameRateControl),
+	FrameRateControlPartial(FrameRateControl),
+	DisplayInversionControl(u8),
+	BlankingPorchControl { vfp: u8, vbp: u8, hfp: u8, hbp: u8 },
+	/// Display Function Control, with `pcdiv` false for the common
+	/// three-parameter form that leaves PCDIV unchanged.
+	DisplayFunctionControl { value: DisplayFunctionControl, pcdiv: bool },
+	EntryModeSet(EntryMode),
+	PowerControl1(PowerControl1),
+	PowerControl2(PowerControl2),
+	VcomControl1(VcomControl1),
+	VcomControl2(VcomControl2),
+	PositiveGammaCorrection(GammaCurve),
+	NegativeGammaCorrection(GammaCurve),
+	DigitalGammaControl1([u8; 16]),
+	DigitalGammaControl2([u8; 64]),
+	InterfaceControl([u8; 3]),
+	PowerControlA([u8; 5]),
+	PowerControlB([u8; 3]),
+	DriverTimingControlA([u8; 3]),
+	DriverTimingControlB([u8; 2]),
+	PowerOnSequenceControl([u8; 4]),
+	Enable3Gamma(u8),
+	PumpRatioControl(u8),
+	/// A command byte not in the standard or extended command set.
+	Other { command: u8, parameters: Parameters },
+	/// A known command followed by fewer parameters than it takes.
+	Incomplete { command: u8, parameters: Parameters },
+	/// Data bytes belonging to no parameter list: pixels after a memory
+	/// write, data read back, or stray bytes.
+	Data { count: usize },
+}
+
+fn be_u16(data: &[u8], index: usize) -> u16 {
+	((data[index] as u16) << 8) | data[index + 1] as u16
+}
+
+fn array<const N: usize>(data: &[u8]) -> [u8; N] {
+	let mut bytes = [0; N];
+	bytes.copy_from_slice(data);
+	bytes
+}
+
+/// Decode `command` given as many parameters as `length` calls for.
+fn decode(command: u8, p: &[u8]) -> Command {
+	match command {
+		0x00 => Command::Nop,
+		0x01 => Command::SoftwareReset,
+		0x04 => Command::ReadDisplayIdentification,
+		0x09 => Command::ReadDisplayStatus,
+		0x0a => Command::ReadDisplayPowerMode,
+		0x0b => Command::ReadDisplayMadctl,
+		0x0c => Command::ReadPixelFormat,
+		0x0d => Command::ReadImageFormat,
+		0x0e => Command::ReadSignalMode,
+		0x0f => Command::ReadSelfDiagnosticResult,
+		0x10 => Command::EnterSleepMode,
+		0x11 => Command::SleepOut,
+		0x12 => Command::PartialModeOn,
+		0x13 => Command::NormalDisplayModeOn,
+		0x20 => Command::DisplayInversion(false),
+		0x21 => Command::DisplayInversion(true),
+		0x26 => Command::GammaSet(p[0]),
+		0x28 => Command::Display(false),
+		0x29 => Command::Display(true),
+		0x2a => Command::ColumnAddressSet { sc: be_u16(p, 0), ec: be_u16(p, 2) },
+		0x2b => Command::PageAddressSet { sp: be_u16(p, 0), ep: be_u16(p, 2) },
+		0x2c => Command::MemoryWriteStart,
+		0x2d => {
+			let mut lut = [0; 128];
+			lut.copy_from_slice(p);
+			Command::ColorSet(lut)
+		},
+		0x2e => Command::MemoryReadStart,
+		0x30 => Command::PartialArea { sr: be_u16(p, 0), er: be_u16(p, 2) },
+		0x33 => Command::VerticalScrollingDefinition { tfa: be_u16(p, 0), vsa: be_u16(p, 2), bfa: be_u16(p, 4) },
+		0x34 => Command::TearingEffect(TearingEffect::Off),
+		0x35 => Command::TearingEffect(match p[0] & 0x01 {
+			0 => TearingEffect::VBlankOnly,
+			_ => TearingEffect::HAndVBlank,
+		}),
+		0x36 => Command::MemoryAccessControl(MemoryAccessControl { raw: [p[0]] }),
+		0x37 => Command::VerticalScrollingStartAddress { vsp: be_u16(p, 0) },
+		0x38 => Command::IdleMode(false),
+		0x39 => Command::IdleMode(true),
+		0x3a => Command::PixelFormatSet(PixelFormat { raw: [p[0]] }),
+		0x3c => Command::WriteMemoryContinue,
+		0x3e => Command::ReadMemoryContinue,
+		0x44 => Command::SetTearScanline { sts: be_u16(p, 0) },
+		0x45 => Command::GetScanline,
+		0x51 => Command::WriteDisplayBrightness(p[0]),
+		0x52 => Command::ReadDisplayBrightness,
+		0x53 => Command::WriteCtrlDisplay(p[0]),
+		0x54 => Command::ReadCtrlDisplay,
+		0x55 => Command::WriteCabc(p[0]),
+		0x56 => Command::ReadCabc,
+		0x5e => Command::WriteCabcMinimumBrightness(p[0]),
+		0x5f => Command::ReadCabcMinimumBrightness,
+		0xda => Command::ReadId1,
+		0xdb => Command::ReadId2,
+		0xdc => Command::ReadId3,
+		0xd3 => Command::ReadId4,
+		0xb0 => Command::RgbInterfaceSignalControl(p[0]),
+		0xb1 => Command::FrameRateControlNormal(FrameRateControl { raw: [p[0], p[1]] }),
+		0xb2 => Command::FrameRateControlIdle(FrameRateControl { raw: [p[0], p[1]] }),
+		0xb3 => Command::FrameRateControlPartial(FrameRateControl { raw: [p[0], p[1]] }),
+		0xb4 => Command::DisplayInversionControl(p[0]),
+		0xb5 => Command::BlankingPorchControl { vfp: p[0], vbp: p[1], hfp: p[2], hbp: p[3] },
+		0xb6 => Command::DisplayFunctionControl {
+			value: DisplayFunctionControl { raw: [p[0], p[1], p[2], p.get(3).copied().unwrap_or(0)] },
+			pcdiv: p.len() == 4,
+		},
+		0xb7 => Command::EntryModeSet(EntryMode { raw: [p[0]] }),
+		0xc0 => Command::PowerControl1(PowerControl1 { raw: [p[0]] }),
+		0xc1 => Command::PowerControl2(PowerControl2 { raw: [p[0]] }),
+		0xc5 => Command::VcomControl1(VcomControl1 { raw: [p[0], p[1]] }),
+		0xc7 => Command::VcomControl2(VcomControl2 { raw: [p[0]] }),
+		0xe0 | 0xe1 => {
+			let mut bytes = [0; 15];
+			bytes.copy_from_slice(p);
+			match command {
+				0xe0 => Command::PositiveGammaCorrection(GammaCurve::from_bytes(bytes)),
+				_ => Command::NegativeGammaCorrection(GammaCurve::from_bytes(bytes)),
+			}
+		},
+		0xe2 => {
+			let mut data = [0; 16];
+			data.copy_from_slice(p);
+			Command::DigitalGammaControl1(data)
+		},
+		0xe3 => {
+			let mut data = [0; 64];
+			data.copy_from_slice(p);
+			Command::DigitalGammaControl2(data)
+		},
+		0xf6 => Command::InterfaceControl(array(p)),
+		0xcb => Command::PowerControlA(array(p)),
+		0xcf => Command::PowerControlB(array(p)),
+		0xe8 => Command::DriverTimingControlA(array(p)),
+		0xea => Command::DriverTimingControlB(array(p)),
+		0xed => Command::PowerOnSequenceControl(array(p)),
+		0xf2 => Command::Enable3Gamma(p[0]),
+		0xf7 => Command::PumpRatioControl(p[0]),
+		_ => {
+			let mut parameters = Parameters::default();
+			for &byte in p {
+				parameters.push(byte);
+			}
+			Command::Other { command, parameters }
+		},
+	}
+}
+
+fn on_off(on: bool) -> &'static str {
+	match on {
+		false => "OFF",
+		true  => "ON",
+	}
+}
+
+/// Frame Rate Control parameters, formatted for Command's Display.
+struct FrameRate(FrameRateControl);
+
+impl fmt::Display for FrameRate {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "division={:?} clocks_per_line={}", self.0.division(), self.0.clocks_per_line())
+	}
+}
+
+impl fmt::Display for Command {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			Command::Nop => write!(f, "NOP"),
+			Command::SoftwareReset => write!(f, "SWRESET"),
+			Command::ReadDisplayIdentification => write!(f, "RDDIDIF"),
+			Command::ReadDisplayStatus => write!(f, "RDDST"),
+			Command::ReadDisplayPowerMode => write!(f, "RDDPM"),
+			Command::ReadDisplayMadctl => write!(f, "RDDMADCTL"),
+			Command::ReadPixelFormat => write!(f, "RDDCOLMOD"),
+			Command::ReadImageFormat => write!(f, "RDDIM"),
+			Command::ReadSignalMode => write!(f, "RDDSM"),
+			Command::ReadSelfDiagnosticResult => write!(f, "RDDSDR"),
+			Command::EnterSleepMode => write!(f, "SLPIN"),
+			Command::SleepOut => write!(f, "SLPOUT"),
+			Command::PartialModeOn => write!(f, "PTLON"),
+			Command::NormalDisplayModeOn => write!(f, "NORON"),
+			Command::DisplayInversion(on) => write!(f, "DINV{}", on_off(on)),
+			Command::GammaSet(gc) => write!(f, "GAMSET gc={:#04x}", gc),
+			Command::Display(on) => write!(f, "DISP{}", on_off(on)),
+			Command::ColumnAddressSet { sc, ec } => write!(f, "CASET sc={} ec={}", sc, ec),
+			Command::PageAddressSet { sp, ep } => write!(f, "PASET sp={} ep={}", sp, ep),
+			Command::MemoryWriteStart => write!(f, "RAMWR"),
+			Command::ColorSet(ref lut) => write!(f, "RGBSET {:02x?}", &lut[..]),
+			Command::MemoryReadStart => write!(f, "RAMRD"),
+			Command::PartialArea { sr, er } => write!(f, "PTLAR sr={} er={}", sr, er),
+			Command::VerticalScrollingDefinition { tfa, vsa, bfa } =>
+				write!(f, "VSCRDEF tfa={} vsa={} bfa={}", tfa, vsa, bfa),
+			Command::TearingEffect(TearingEffect::Off) => write!(f, "TEOFF"),
+			Command::TearingEffect(mode) => write!(f, "TEON {:?}", mode),
+			Command::MemoryAccessControl(value) => write!(f,
+				"MADCTL my={} mx={} mv={} ml={} bgr={} mh={}",
+				value.row_address_order() as u8,
+				value.column_address_order() as u8,
+				value.row_column_exchange() as u8,
+				value.vertical_refresh_order() as u8,
+				value.bgr() as u8,
+				value.horizontal_refresh_order() as u8,
+			),
+			Command::VerticalScrollingStartAddress { vsp } => write!(f, "VSCRSADD vsp={}", vsp),
+			Command::IdleMode(on) => write!(f, "IDM{}", on_off(on)),
+			Command::PixelFormatSet(value) => {
+				write!(f, "PIXSET")?;
+				for (name, bpp) in [("dpi", value.dpi()), ("dbi", value.dbi())] {
+					match bpp {
+						Some(bpp) => write!(f, " {}={}", name, bpp.bits())?,
+						None => write!(f, " {}=reserved", name)?,
+					}
+				}
+				Ok(())
+			},
+			Command::WriteMemoryContinue => write!(f, "RAMWRC"),
+			Command::ReadMemoryContinue => write!(f, "RAMRDC"),
+			Command::SetTearScanline { sts } => write!(f, "STE sts={}", sts),
+			Command::GetScanline => write!(f, "GSCAN"),
+			Command::WriteDisplayBrightness(dbv) => write!(f, "WRDISBV dbv={:#04x}", dbv),
+			Command::ReadDisplayBrightness => write!(f, "RDDISBV"),
+			Command::WriteCtrlDisplay(value) => write!(f, "WRCTRLD {:#04x}", value),
+			Command::ReadCtrlDisplay => write!(f, "RDCTRLD"),
+			Command::WriteCabc(c) => write!(f, "WRCABC c={}", c),
+			Command::ReadCabc => write!(f, "RDCABC"),
+			Command::WriteCabcMinimumBrightness(cmb) => write!(f, "WRCABCMB cmb={:#04x}", cmb),
+			Command::ReadCabcMinimumBrightness => write!(f, "RDCABCMB"),
+			Command::ReadId1 => write!(f, "RDID1"),
+			Command::ReadId2 => write!(f, "RDID2"),
+			Command::ReadId3 => write!(f, "RDID3"),
+			Command::ReadId4 => write!(f, "RDID4"),
+			Command::RgbInterfaceSignalControl(value) => write!(f, "IFMODE {:#04x}", value),
+			Command::FrameRateControlNormal(value) => write!(f, "FRMCTR1 {}", FrameRate(value)),
+			Command::FrameRateControlIdle(value) => write!(f, "FRMCTR2 {}", FrameRate(value)),
+			Command::FrameRateControlPartial(value) => write!(f, "FRMCTR3 {}", FrameRate(value)),
+			Command::DisplayInversionControl(value) => write!(f, "INVTR {:#04x}", value),
+			Command::BlankingPorchControl { vfp, vbp, hfp, hbp } =>
+				write!(f, "PRCTR vfp={} vbp={} hfp={} hbp={}", vfp, vbp, hfp, hbp),
+			Command::DisplayFunctionControl { value, pcdiv } => match pcdiv {
+				false => write!(f, "DISCTRL {:02x?}", &value.raw[..3]),
+				true  => write!(f, "DISCTRL {:02x?}", value.raw),
+			},
+			Command::EntryModeSet(value) => write!(f, "ETMOD {:02x?}", value.raw),
+			Command::PowerControl1(value) => match value.gvdd_millivolts() {
+				Some(gvdd) => write!(f, "PWCTRL1 gvdd={}mV", gvdd),
+				None => write!(f, "PWCTRL1 {:02x?}", value.raw),
+			},
+			Command::PowerControl2(value) => write!(f, "PWCTRL2 {:02x?}", value.raw),
+			Command::VcomControl1(value) => match (value.vcomh_millivolts(), value.vcoml_millivolts()) {
+				(Some(vcomh), Some(vcoml)) => write!(f, "VMCTRL1 vcomh={}mV vcoml={}mV", vcomh, vcoml),
+				_ => write!(f, "VMCTRL1 {:02x?}", value.raw),
+			},
+			Command::VcomControl2(value) => match value.offset() {
+				Some(offset) => write!(f, "VMCTRL2 offset={}", offset),
+				None => write!(f, "VMCTRL2 offset=off"),
+			},
+			Command::PositiveGammaCorrection(ref curve) => write!(f, "PGAMCTRL {:02x?}", curve.to_bytes()),
+			Command::NegativeGammaCorrection(ref curve) => write!(f, "NGAMCTRL {:02x?}", curve.to_bytes()),
+			Command::DigitalGammaControl1(ref data) => write!(f, "DGAMCTRL1 {:02x?}", data),
+			Command::DigitalGammaControl2(ref data) => write!(f, "DGAMCTRL2 {:02x?}", &data[..]),
+			Command::InterfaceControl(ref data) => write!(f, "IFCTL {:02x?}", data),
+			Command::PowerControlA(ref data) => write!(f, "PWCTRLA {:02x?}", data),
+			Command::PowerControlB(ref data) => write!(f, "PWCTRLB {:02x?}", data),
+			Command::DriverTimingControlA(ref data) => write!(f, "DTCTRLA {:02x?}", data),
+			Command::DriverTimingControlB(ref data) => write!(f, "DTCTRLB {:02x?}", data),
+			Command::PowerOnSequenceControl(ref data) => write!(f, "PWONSEQ {:02x?}", data),
+			Command::Enable3Gamma(value) => write!(f, "EN3G {:#04x}", value),
+			Command::PumpRatioControl(value) => write!(f, "PUMPRC {:#04x}", value),
+			Command::Other { command, ref parameters } => write!(f, "{:02X}h {:?}", command, parameters),
+			Command::Incomplete { command, ref parameters } =>
+				write!(f, "{:02X}h incomplete {:?}", command, parameters),
+			Command::Data { count } => write!(f, "{} data bytes", count),
+		}
+	}
+}
+
+/// Incremental decoder for a captured byte stream.
+///
+/// Feed each byte with `push`, then drain the decoded commands with `pop`
+/// before pushing the next: a byte completes at most two commands. Call
+/// `finish` at the end of the capture to flush the last one.
+#[derive(Clone, Debug, Default)]
+pub struct Decoder {
+	command: Option<u8>,
+	parameters: Parameters,
+	data: usize,
+	queue: [Option<Command>; 2],
+}
+
+impl Decoder {
+	pub fn new() -> Decoder {
+		Decoder::default()
+	}
+
+	/// Feed one byte, with `command` true when D/CX was low.
+	pub fn push(&mut self, command: bool, byte: u8) {
+		match command {
+			false => self.parameter(byte),
+			true => {
+				self.flush();
+				self.start(byte);
+			},
+		}
+	}
+
+	/// Flush a command still waiting for parameters, and trailing data.
+	pub fn finish(&mut self) {
+		self.flush();
+	}
+
+	/// Next decoded command, oldest first.
+	pub fn pop(&mut self) -> Option<Command> {
+		let next = self.queue[0].take();
+		self.queue.swap(0, 1);
+		next
+	}
+
+	fn emit(&mut self, command: Command) {
+		match self.queue[0] {
+			None => self.queue[0] = Some(command),
+			Some(_) => self.queue[1] = Some(command),
+		}
+	}
+
+	fn start(&mut self, command: u8) {
+		self.parameters = Parameters::default();
+		match length(command) {
+			Length::Fixed(0) | Length::Stream => self.emit(decode(command, &[])),
+			_ => self.command = Some(command),
+		}
+	}
+
+	fn parameter(&mut self, byte: u8) {
+		let command = match self.command {
+			Some(command) => command,
+			None => {
+				self.data += 1;
+				return;
+			},
+		};
+		let stored = self.parameters.push(byte);
+		match length(command) {
+			Length::Fixed(n) | Length::Range(_, n) if self.parameters.len == n => {
+				self.command = None;
+				let parameters = self.parameters;
+				self.emit(decode(command, parameters.as_slice()));
+			},
+			Length::Unknown if !stored => {
+				self.command = None;
+				self.emit(Command::Other { command, parameters: self.parameters });
+				self.data += 1;
+			},
+			_ => (),
+		}
+	}
+
+	fn flush(&mut self) {
+		if let Some(command) = self.command.take() {
+			let parameters = self.parameters;
+			self.emit(match length(command) {
+				Length::Range(n, _) if parameters.len >= n => decode(command, parameters.as_slice()),
+				Length::Unknown => Command::Other { command, parameters },
+				_ => Command::Incomplete { command, parameters },
+			});
+		}
+		if self.data > 0 {
+			self.emit(Command::Data { count: self.data });
+			self.data = 0;
+		}
+	}
+}
+
+/// Iterator over the commands decoded from a byte stream, returned by
+/// `decode`.
+pub struct Decode<I> {
+	bytes: I,
+	decoder: Decoder,
+	finished: bool,
+}
+
+impl<I: Iterator<Item=(bool, u8)>> Iterator for Decode<I> {
+	type Item = Command;
+
+	fn next(&mut self) -> Option<Command> {
+		loop {
+			if let Some(command) = self.decoder.pop() {
+				return Some(command);
+			}
+			if self.finished {
+				return None;
+			}
+			match self.bytes.next() {
+				Some((command, byte)) => self.decoder.push(command, byte),
+				None => {
+					self.decoder.finish();
+					self.finished = true;
+				},
+			}
+		}
+	}
+}
+
+/// Decode `bytes`, each paired with true if D/CX was low (a command byte)
+/// or false for a data byte.
+pub fn decode_stream<I>(bytes: I) -> Decode<I::IntoIter>
+	where I: IntoIterator<Item=(bool, u8)>
+{
+	Decode {
+		bytes: bytes.into_iter(),
+		decoder: Decoder::new(),
+		finished: false,
+	}
+}
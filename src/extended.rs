@@ -0,0 +1,450 @@
+//! Extended command set: panel power, VCOM, frame rate and display timing
+//! registers (B0h-CFh).
+
+#[cfg(feature = "async")]
+use crate::asynch::{AsyncController, AsyncInterface};
+use crate::{Controller, Error, Interface, ResetPin};
+
+/// Power Control 1 (C0h) parameter, setting the GVDD level that the gamma
+/// circuit references.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PowerControl1 {
+	pub(crate) raw: [u8; 1],
+}
+
+impl PowerControl1 {
+	/// GVDD in millivolts, from 3000 to 6000 in 50 mV steps. Returns None
+	/// if out of range; values between steps round down.
+	pub fn new(gvdd_millivolts: u16) -> Option<PowerControl1> {
+		match gvdd_millivolts {
+			3000..=6000 => Some(PowerControl1 {
+				raw: [((gvdd_millivolts - 3000) / 50) as u8 + 0x03],
+			}),
+			_ => None,
+		}
+	}
+
+	/// GVDD in millivolts, or None for the reserved VRH values 00h-02h.
+	pub fn gvdd_millivolts(&self) -> Option<u16> {
+		match self.raw[0] & 0x3f {
+			0x00..=0x02 => None,
+			vrh => Some(3000 + (vrh as u16 - 0x03) * 50),
+		}
+	}
+}
+
+/// The reset value, GVDD = 4.50 V.
+impl Default for PowerControl1 {
+	fn default() -> PowerControl1 {
+		PowerControl1 { raw: [0x21] }
+	}
+}
+
+/// Step-up factors for the operating voltages, as multiples of VCI.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum StepUpFactor {
+	/// VGH = VCI x 7, VGL = -VCI x 4
+	Vgh7Vgl4,
+	/// VGH = VCI x 7, VGL = -VCI x 3
+	Vgh7Vgl3,
+	/// VGH = VCI x 6, VGL = -VCI x 4
+	Vgh6Vgl4,
+	/// VGH = VCI x 6, VGL = -VCI x 3
+	Vgh6Vgl3,
+}
+
+/// Power Control 2 (C1h) parameter, selecting the step-up circuit
+/// factors. AVDD is always VCI x 2.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PowerControl2 {
+	pub(crate) raw: [u8; 1],
+}
+
+impl PowerControl2 {
+	pub fn new(factor: StepUpFactor) -> PowerControl2 {
+		let bt = match factor {
+			StepUpFactor::Vgh7Vgl4 => 0b000,
+			StepUpFactor::Vgh7Vgl3 => 0b001,
+			StepUpFactor::Vgh6Vgl4 => 0b010,
+			StepUpFactor::Vgh6Vgl3 => 0b011,
+		};
+		PowerControl2 { raw: [0x10 | bt] }
+	}
+}
+
+/// The reset value, VGH = VCI x 7 and VGL = -VCI x 4.
+impl Default for PowerControl2 {
+	fn default() -> PowerControl2 {
+		PowerControl2::new(StepUpFactor::Vgh7Vgl4)
+	}
+}
+
+/// VCOM Control 1 (C5h) parameters, setting the VCOMH and VCOML levels.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct VcomControl1 {
+	pub(crate) raw: [u8; 2],
+}
+
+impl VcomControl1 {
+	/// VCOMH in millivolts from 2700 to 5200, and VCOML in millivolts from
+	/// -2500 to 0, both in 25 mV steps. Returns None if either is out of
+	/// range; values between steps round down.
+	pub fn new(vcomh_millivolts: u16, vcoml_millivolts: i16) -> Option<VcomControl1> {
+		match (vcomh_millivolts, vcoml_millivolts) {
+			(2700..=5200, -2500..=0) => Some(VcomControl1 {
+				raw: [
+					((vcomh_millivolts - 2700) / 25) as u8,
+					((vcoml_millivolts + 2500) / 25) as u8,
+				],
+			}),
+			_ => None,
+		}
+	}
+
+	/// VCOMH in millivolts, or None for the reserved VMH values 65h-7Fh.
+	pub fn vcomh_millivolts(&self) -> Option<u16> {
+		match self.raw[0] & 0x7f {
+			vmh @ 0x00..=0x64 => Some(2700 + vmh as u16 * 25),
+			_ => None,
+		}
+	}
+
+	/// VCOML in millivolts, or None for the reserved VML values 65h-7Fh.
+	pub fn vcoml_millivolts(&self) -> Option<i16> {
+		match self.raw[1] & 0x7f {
+			vml @ 0x00..=0x64 => Some(-2500 + vml as i16 * 25),
+			_ => None,
+		}
+	}
+}
+
+/// The reset value, VCOMH = 3.925 V and VCOML = -1.000 V.
+impl Default for VcomControl1 {
+	fn default() -> VcomControl1 {
+		VcomControl1 { raw: [0x31, 0x3c] }
+	}
+}
+
+/// VCOM Control 2 (C7h) parameter, applying an offset to the VCOMH and
+/// VCOML levels set by VCOM Control 1.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct VcomControl2 {
+	pub(crate) raw: [u8; 1],
+}
+
+impl VcomControl2 {
+	/// Offset both levels by `offset` steps (-64 to 63) of 25 mV.
+	pub fn new(offset: i8) -> Option<VcomControl2> {
+		match offset {
+			-64..=63 => Some(VcomControl2 { raw: [0x80 | (offset + 64) as u8] }),
+			_ => None,
+		}
+	}
+
+	/// Use the VCOM Control 1 levels unmodified (nVM = 0).
+	pub fn disabled() -> VcomControl2 {
+		VcomControl2 { raw: [0x00] }
+	}
+
+	/// Offset in 25 mV steps, or None if disabled.
+	pub fn offset(&self) -> Option<i8> {
+		match self.raw[0] & 0x80 {
+			0 => None,
+			_ => Some((self.raw[0] & 0x7f) as i8 - 64),
+		}
+	}
+}
+
+/// The reset value, an offset of zero.
+impl Default for VcomControl2 {
+	fn default() -> VcomControl2 {
+		VcomControl2 { raw: [0xc0] }
+	}
+}
+
+/// Internal clock division ratio applied to fosc.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DivisionRatio {
+	Div1,
+	Div2,
+	Div4,
+	Div8,
+}
+
+/// Frame Rate Control (B1h, B2h, B3h) parameters. The frame rate is
+/// fosc / (clocks per line x division ratio x (lines + VBP + VFP)), with
+/// fosc = 615 kHz.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FrameRateControl {
+	pub(crate) raw: [u8; 2],
+}
+
+impl FrameRateControl {
+	/// `clocks_per_line` ranges from 16 to 31. Returns None if out of
+	/// range.
+	pub fn new(division: DivisionRatio, clocks_per_line: u8) -> Option<FrameRateControl> {
+		let div = match division {
+			DivisionRatio::Div1 => 0b00,
+			DivisionRatio::Div2 => 0b01,
+			DivisionRatio::Div4 => 0b10,
+			DivisionRatio::Div8 => 0b11,
+		};
+		match clocks_per_line {
+			16..=31 => Some(FrameRateControl { raw: [div, clocks_per_line] }),
+			_ => None,
+		}
+	}
+
+	pub fn division(&self) -> DivisionRatio {
+		match self.raw[0] & 0x03 {
+			0b00 => DivisionRatio::Div1,
+			0b01 => DivisionRatio::Div2,
+			0b10 => DivisionRatio::Div4,
+			_    => DivisionRatio::Div8,
+		}
+	}
+
+	pub fn clocks_per_line(&self) -> u8 {
+		self.raw[1]
+	}
+}
+
+/// The reset value, fosc / 1 at 27 clocks per line (70 Hz).
+impl Default for FrameRateControl {
+	fn default() -> FrameRateControl {
+		FrameRateControl { raw: [0x00, 0x1b] }
+	}
+}
+
+/// Gate driver scan mode in the non-display area (PTG).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum NonDisplayScan {
+	Normal,
+	Interval,
+}
+
+/// Source and VCOM output levels in the non-display area (PT), given as
+/// positive/negative polarity source level and VCOM level.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum NonDisplayOutput {
+	/// V63/V0 sources, VCOML/VCOMH.
+	V63V0,
+	/// V0/V63 sources, VCOML/VCOMH.
+	V0V63,
+	/// AGND sources and VCOM.
+	Agnd,
+	/// Hi-Z sources, AGND VCOM.
+	HiZ,
+}
+
+/// Display Function Control (B6h) parameters, set up with the `with_*`
+/// methods starting from the reset value.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct DisplayFunctionControl {
+	pub(crate) raw: [u8; 4],
+}
+
+impl DisplayFunctionControl {
+	pub fn new() -> DisplayFunctionControl {
+		DisplayFunctionControl::default()
+	}
+
+	fn with_flag(mut self, flag: u8, on: bool) -> DisplayFunctionControl {
+		match on {
+			false => self.raw[1] &= !flag,
+			true  => self.raw[1] |= flag,
+		}
+		self
+	}
+
+	/// Gate and source/VCOM behaviour in the non-display area (PTG, PT).
+	pub fn with_non_display_area(mut self, scan: NonDisplayScan, output: NonDisplayOutput) -> DisplayFunctionControl {
+		let ptg = match scan {
+			NonDisplayScan::Normal   => 0b00,
+			NonDisplayScan::Interval => 0b10,
+		};
+		let pt = match output {
+			NonDisplayOutput::V63V0 => 0b00,
+			NonDisplayOutput::V0V63 => 0b01,
+			NonDisplayOutput::Agnd  => 0b10,
+			NonDisplayOutput::HiZ   => 0b11,
+		};
+		self.raw[0] = (ptg << 2) | pt;
+		self
+	}
+
+	/// Liquid crystal type (REV): normally white when set.
+	pub fn with_normally_white(self, on: bool) -> DisplayFunctionControl {
+		self.with_flag(0x80, on)
+	}
+
+	/// Gate output scan direction (GS): G320 to G1 when set.
+	pub fn with_gate_scan_reversed(self, on: bool) -> DisplayFunctionControl {
+		self.with_flag(0x40, on)
+	}
+
+	/// Source output scan direction (SS): S720 to S1 when set.
+	pub fn with_source_scan_reversed(self, on: bool) -> DisplayFunctionControl {
+		self.with_flag(0x20, on)
+	}
+
+	/// Gate scan mode (SM): alternate odd and even gate lines when set.
+	pub fn with_alternate_gate_scan(self, on: bool) -> DisplayFunctionControl {
+		self.with_flag(0x10, on)
+	}
+
+	/// Scan cycle (ISC) for interval scan, in frames: an odd number from 1
+	/// to 31. Returns None if out of range.
+	pub fn with_scan_cycle_frames(mut self, frames: u8) -> Option<DisplayFunctionControl> {
+		match frames {
+			1..=31 if frames % 2 == 1 => {
+				self.raw[1] = (self.raw[1] & 0xf0) | (frames / 2);
+				Some(self)
+			},
+			_ => None,
+		}
+	}
+
+	/// Number of lines to drive (NL): a multiple of 8 from 8 to 320.
+	/// Returns None if out of range.
+	pub fn with_lines(mut self, lines: u16) -> Option<DisplayFunctionControl> {
+		match lines {
+			8..=320 if lines % 8 == 0 => {
+				self.raw[2] = (lines / 8 - 1) as u8;
+				Some(self)
+			},
+			_ => None,
+		}
+	}
+
+	/// External fosc divider (PCDIV), 0 to 63: fosc = DOTCLK / (2 x
+	/// (PCDIV + 1)).
+	pub fn with_pcdiv(mut self, pcdiv: u8) -> DisplayFunctionControl {
+		self.raw[3] = pcdiv & 0x3f;
+		self
+	}
+}
+
+/// The reset value: interval scan, 320 lines, normally white.
+impl Default for DisplayFunctionControl {
+	fn default() -> DisplayFunctionControl {
+		DisplayFunctionControl { raw: [0x0a, 0x82, 0x27, 0x00] }
+	}
+}
+
+/// Gate driver output levels (GON, DTE).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GateOutput {
+	/// All gate outputs at VGH.
+	Vgh,
+	/// All gate outputs at VGL.
+	Vgl,
+	/// Normal display.
+	Normal,
+}
+
+/// Entry Mode Set (B7h) parameter.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct EntryMode {
+	pub(crate) raw: [u8; 1],
+}
+
+impl EntryMode {
+	pub fn new(gate_output: GateOutput, low_voltage_detection: bool) -> EntryMode {
+		let gon_dte = match gate_output {
+			GateOutput::Vgh    => 0b00,
+			GateOutput::Vgl    => 0b10,
+			GateOutput::Normal => 0b11,
+		};
+		// GAS disables low voltage detection when set.
+		let gas = match low_voltage_detection {
+			false => 1,
+			true  => 0,
+		};
+		EntryMode { raw: [(gon_dte << 1) | gas] }
+	}
+}
+
+/// The reset value: normal display, low voltage detection enabled.
+impl Default for EntryMode {
+	fn default() -> EntryMode {
+		EntryMode::new(GateOutput::Normal, true)
+	}
+}
+
+impl<T: Interface, RST: ResetPin> Controller<T, RST> {
+	pub fn frame_rate_control_normal(&mut self, value: FrameRateControl) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_parameters(0xb1, &value.raw)
+	}
+
+	pub fn frame_rate_control_idle(&mut self, value: FrameRateControl) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_parameters(0xb2, &value.raw)
+	}
+
+	pub fn frame_rate_control_partial(&mut self, value: FrameRateControl) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_parameters(0xb3, &value.raw)
+	}
+
+	pub fn display_function_control(&mut self, value: DisplayFunctionControl) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_parameters(0xb6, &value.raw)
+	}
+
+	pub fn entry_mode_set(&mut self, value: EntryMode) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_parameters(0xb7, &value.raw)
+	}
+
+	pub fn power_control_1(&mut self, value: PowerControl1) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_parameters(0xc0, &value.raw)
+	}
+
+	pub fn power_control_2(&mut self, value: PowerControl2) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_parameters(0xc1, &value.raw)
+	}
+
+	pub fn vcom_control_1(&mut self, value: VcomControl1) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_parameters(0xc5, &value.raw)
+	}
+
+	pub fn vcom_control_2(&mut self, value: VcomControl2) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_parameters(0xc7, &value.raw)
+	}
+}
+
+#[cfg(feature = "async")]
+impl<T: AsyncInterface, RST: ResetPin> AsyncController<T, RST> {
+	pub async fn frame_rate_control_normal(&mut self, value: FrameRateControl) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_parameters(0xb1, &value.raw).await
+	}
+
+	pub async fn frame_rate_control_idle(&mut self, value: FrameRateControl) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_parameters(0xb2, &value.raw).await
+	}
+
+	pub async fn frame_rate_control_partial(&mut self, value: FrameRateControl) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_parameters(0xb3, &value.raw).await
+	}
+
+	pub async fn display_function_control(&mut self, value: DisplayFunctionControl) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_parameters(0xb6, &value.raw).await
+	}
+
+	pub async fn entry_mode_set(&mut self, value: EntryMode) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_parameters(0xb7, &value.raw).await
+	}
+
+	pub async fn power_control_1(&mut self, value: PowerControl1) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_parameters(0xc0, &value.raw).await
+	}
+
+	pub async fn power_control_2(&mut self, value: PowerControl2) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_parameters(0xc1, &value.raw).await
+	}
+
+	pub async fn vcom_control_1(&mut self, value: VcomControl1) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_parameters(0xc5, &value.raw).await
+	}
+
+	pub async fn vcom_control_2(&mut self, value: VcomControl2) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_parameters(0xc7, &value.raw).await
+	}
+}
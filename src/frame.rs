@@ -0,0 +1,244 @@
+//! Captured images of the simulated panel: PNG and PPM output, and
+//! comparison against stored golden images.
+
+use core::fmt;
+use std::io::{self, Read, Write};
+use std::vec;
+use std::vec::Vec;
+
+/// An RGB image with 8 bits per channel, as produced by
+/// `SimulatedPanel::frame`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Frame {
+	width: u16,
+	height: u16,
+	pixels: Vec<[u8; 3]>,
+}
+
+/// How a frame differs from its golden image.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FrameDiff {
+	/// The frames have different dimensions.
+	Size {
+		actual: (u16, u16),
+		expected: (u16, u16),
+	},
+	/// `count` pixels differ by more than the tolerance, the first (in
+	/// row order) at `first`. `max_delta` is the largest channel
+	/// difference seen.
+	Pixels {
+		count: usize,
+		first: (u16, u16),
+		max_delta: u8,
+	},
+}
+
+impl fmt::Display for FrameDiff {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			FrameDiff::Size { actual, expected } =>
+				write!(f, "frame is {}x{}, expected {}x{}", actual.0, actual.1, expected.0, expected.1),
+			FrameDiff::Pixels { count, first, max_delta } =>
+				write!(f, "{} pixels differ, first at ({}, {}), by up to {}", count, first.0, first.1, max_delta),
+		}
+	}
+}
+
+impl Frame {
+	/// A black frame.
+	pub fn new(width: u16, height: u16) -> Frame {
+		Frame {
+			width,
+			height,
+			pixels: vec![[0; 3]; width as usize * height as usize],
+		}
+	}
+
+	pub fn width(&self) -> u16 {
+		self.width
+	}
+
+	pub fn height(&self) -> u16 {
+		self.height
+	}
+
+	/// Red, green and blue at `x`, `y`.
+	pub fn pixel(&self, x: u16, y: u16) -> [u8; 3] {
+		self.pixels[self.index(x, y)]
+	}
+
+	pub fn set_pixel(&mut self, x: u16, y: u16, rgb: [u8; 3]) {
+		let index = self.index(x, y);
+		self.pixels[index] = rgb;
+	}
+
+	fn index(&self, x: u16, y: u16) -> usize {
+		assert!(x < self.width && y < self.height, "pixel out of bounds");
+		y as usize * self.width as usize + x as usize
+	}
+
+	/// Compare against `golden`, treating channels that differ by at most
+	/// `tolerance` as equal. Returns None if the frames match.
+	pub fn diff(&self, golden: &Frame, tolerance: u8) -> Option<FrameDiff> {
+		if (self.width, self.height) != (golden.width, golden.height) {
+			return Some(FrameDiff::Size {
+				actual: (self.width, self.height),
+				expected: (golden.width, golden.height),
+			});
+		}
+		let mut count = 0;
+		let mut first = None;
+		let mut max_delta = 0;
+		for (index, (a, b)) in self.pixels.iter().zip(golden.pixels.iter()).enumerate() {
+			let delta = a.iter().zip(b.iter()).map(|(a, b)| a.abs_diff(*b)).max().unwrap_or(0);
+			if delta > tolerance {
+				count += 1;
+				first.get_or_insert(index);
+				max_delta = max_delta.max(delta);
+			}
+		}
+		let width = self.width as usize;
+		first.map(|index| FrameDiff::Pixels {
+			count,
+			first: ((index % width) as u16, (index / width) as u16),
+			max_delta,
+		})
+	}
+
+	/// Write as a binary PPM (P6).
+	pub fn write_ppm<W: Write>(&self, sink: &mut W) -> io::Result<()> {
+		write!(sink, "P6\n{} {}\n255\n", self.width, self.height)?;
+		sink.write_all(self.pixels.as_flattened())
+	}
+
+	/// Read a binary PPM (P6) with a maximum value of 255, as written by
+	/// `write_ppm`.
+	pub fn read_ppm<R: Read>(source: &mut R) -> io::Result<Frame> {
+		let mut data = Vec::new();
+		source.read_to_end(&mut data)?;
+		let mut fields = PpmFields { data: &data, position: 0 };
+		if fields.next() != Some(&b"P6"[..]) {
+			return Err(invalid_data("not a binary PPM"));
+		}
+		let width = fields.number()?;
+		let height = fields.number()?;
+		if fields.number()? != 255 {
+			return Err(invalid_data("unsupported PPM maximum value"));
+		}
+		// A single whitespace character separates the header from the
+		// pixels.
+		let pixels = data.get(fields.position + 1..).unwrap_or(&[]);
+		if pixels.len() as u64 != width as u64 * height as u64 * 3 {
+			return Err(invalid_data("PPM pixel data length mismatch"));
+		}
+		Ok(Frame {
+			width,
+			height,
+			pixels: pixels.chunks_exact(3).map(|rgb| [rgb[0], rgb[1], rgb[2]]).collect(),
+		})
+	}
+
+	/// Write as an uncompressed truecolor PNG.
+	pub fn write_png<W: Write>(&self, sink: &mut W) -> io::Result<()> {
+		sink.write_all(b"\x89PNG\r\n\x1a\n")?;
+
+		let mut header = Vec::with_capacity(13);
+		header.extend_from_slice(&(self.width as u32).to_be_bytes());
+		header.extend_from_slice(&(self.height as u32).to_be_bytes());
+		// 8 bits per channel, truecolor, deflate, adaptive filtering, no
+		// interlace.
+		header.extend_from_slice(&[8, 2, 0, 0, 0]);
+		write_png_chunk(sink, b"IHDR", &header)?;
+
+		let mut scanlines = Vec::with_capacity(self.pixels.len() * 3 + self.height as usize);
+		for row in self.pixels.chunks_exact(self.width.max(1) as usize) {
+			// Filter type None.
+			scanlines.push(0);
+			scanlines.extend_from_slice(row.as_flattened());
+		}
+		write_png_chunk(sink, b"IDAT", &zlib_stored(&scanlines))?;
+		write_png_chunk(sink, b"IEND", &[])
+	}
+}
+
+fn invalid_data(message: &str) -> io::Error {
+	io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Whitespace-separated PPM header fields, skipping `#` comments.
+struct PpmFields<'a> {
+	data: &'a [u8],
+	position: usize,
+}
+
+impl<'a> PpmFields<'a> {
+	fn next(&mut self) -> Option<&'a [u8]> {
+		loop {
+			match self.data.get(self.position)? {
+				b'#' => while self.data.get(self.position).is_some_and(|&b| b != b'\n') {
+					self.position += 1;
+				},
+				b if b.is_ascii_whitespace() => self.position += 1,
+				_ => break,
+			}
+		}
+		let start = self.position;
+		while self.data.get(self.position).is_some_and(|b| !b.is_ascii_whitespace()) {
+			self.position += 1;
+		}
+		Some(&self.data[start..self.position])
+	}
+
+	fn number(&mut self) -> io::Result<u16> {
+		self.next()
+			.and_then(|field| core::str::from_utf8(field).ok())
+			.and_then(|field| field.parse().ok())
+			.ok_or_else(|| invalid_data("bad PPM header"))
+	}
+}
+
+fn write_png_chunk<W: Write>(sink: &mut W, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+	sink.write_all(&(data.len() as u32).to_be_bytes())?;
+	sink.write_all(kind)?;
+	sink.write_all(data)?;
+	let crc = crc32(crc32(!0, kind), data);
+	sink.write_all(&(!crc).to_be_bytes())
+}
+
+/// Continue a CRC-32 (ISO 3309) over `data`, starting from `crc`.
+fn crc32(mut crc: u32, data: &[u8]) -> u32 {
+	for &byte in data {
+		crc ^= byte as u32;
+		for _ in 0..8 {
+			crc = match crc & 1 {
+				0 => crc >> 1,
+				_ => (crc >> 1) ^ 0xedb8_8320,
+			};
+		}
+	}
+	crc
+}
+
+/// Wrap `data` in a zlib stream of uncompressed deflate blocks.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+	const BLOCK_LEN: usize = 0xffff;
+	let mut out = Vec::with_capacity(data.len() + data.len() / BLOCK_LEN * 5 + 11);
+	out.extend_from_slice(&[0x78, 0x01]);
+	let mut blocks = data.chunks(BLOCK_LEN).peekable();
+	if blocks.peek().is_none() {
+		out.extend_from_slice(&[0x01, 0x00, 0x00, 0xff, 0xff]);
+	}
+	while let Some(block) = blocks.next() {
+		out.push(blocks.peek().is_none() as u8);
+		out.extend_from_slice(&(block.len() as u16).to_le_bytes());
+		out.extend_from_slice(&(!(block.len() as u16)).to_le_bytes());
+		out.extend_from_slice(block);
+	}
+	let (mut a, mut b) = (1u32, 0u32);
+	for &byte in data {
+		a = (a + byte as u32) % 65521;
+		b = (b + a) % 65521;
+	}
+	out.extend_from_slice(&((b << 16) | a).to_be_bytes());
+	out
+}
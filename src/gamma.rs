@@ -0,0 +1,168 @@
+//! Gamma correction tables: Positive/Negative Gamma Correction (E0h/E1h)
+//! and Digital Gamma Control (E2h/E3h).
+
+#[cfg(feature = "async")]
+use crate::asynch::{AsyncController, AsyncInterface};
+use crate::{Controller, Error, Interface, ResetPin};
+
+/// One polarity's gamma curve, as the grayscale voltage adjustments V0
+/// through V63 from the module datasheet. Each field is masked to its
+/// register width when sent.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct GammaCurve {
+	pub v63: u8,
+	pub v62: u8,
+	pub v61: u8,
+	pub v59: u8,
+	pub v57: u8,
+	pub v50: u8,
+	pub v43: u8,
+	pub v36: u8,
+	pub v27: u8,
+	pub v20: u8,
+	pub v13: u8,
+	pub v6: u8,
+	pub v4: u8,
+	pub v2: u8,
+	pub v1: u8,
+	pub v0: u8,
+}
+
+impl GammaCurve {
+	/// Decode the 15 parameter bytes as listed in module datasheets and
+	/// vendor init sequences.
+	pub const fn from_bytes(bytes: [u8; 15]) -> GammaCurve {
+		GammaCurve {
+			v63: bytes[0] & 0x0f,
+			v62: bytes[1] & 0x3f,
+			v61: bytes[2] & 0x3f,
+			v59: bytes[3] & 0x0f,
+			v57: bytes[4] & 0x1f,
+			v50: bytes[5] & 0x0f,
+			v43: bytes[6] & 0x7f,
+			v27: bytes[7] >> 4,
+			v36: bytes[7] & 0x0f,
+			v20: bytes[8] & 0x7f,
+			v13: bytes[9] & 0x0f,
+			v6: bytes[10] & 0x1f,
+			v4: bytes[11] & 0x0f,
+			v2: bytes[12] & 0x3f,
+			v1: bytes[13] & 0x3f,
+			v0: bytes[14] & 0x0f,
+		}
+	}
+
+	pub const fn to_bytes(&self) -> [u8; 15] {
+		[
+			self.v63 & 0x0f,
+			self.v62 & 0x3f,
+			self.v61 & 0x3f,
+			self.v59 & 0x0f,
+			self.v57 & 0x1f,
+			self.v50 & 0x0f,
+			self.v43 & 0x7f,
+			((self.v27 & 0x0f) << 4) | (self.v36 & 0x0f),
+			self.v20 & 0x7f,
+			self.v13 & 0x0f,
+			self.v6 & 0x1f,
+			self.v4 & 0x0f,
+			self.v2 & 0x3f,
+			self.v1 & 0x3f,
+			self.v0 & 0x0f,
+		]
+	}
+}
+
+/// A matched pair of positive and negative polarity gamma curves.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct GammaCorrection {
+	pub positive: GammaCurve,
+	pub negative: GammaCurve,
+}
+
+impl GammaCorrection {
+	/// Adafruit 2.8" and 3.2" TFT modules, also the common default in
+	/// Arduino drivers.
+	pub const ADAFRUIT_2_8: GammaCorrection = GammaCorrection {
+		positive: GammaCurve::from_bytes([
+			0x0f, 0x31, 0x2b, 0x0c, 0x0e, 0x08, 0x4e, 0xf1,
+			0x37, 0x07, 0x10, 0x03, 0x0e, 0x09, 0x00,
+		]),
+		negative: GammaCurve::from_bytes([
+			0x00, 0x0e, 0x14, 0x03, 0x11, 0x07, 0x31, 0xc1,
+			0x48, 0x08, 0x0f, 0x0c, 0x31, 0x36, 0x0f,
+		]),
+	};
+
+	/// 2.4" module fitted to the ST STM32F429I-DISCO board.
+	pub const STM32F429_DISCOVERY: GammaCorrection = GammaCorrection {
+		positive: GammaCurve::from_bytes([
+			0x0f, 0x29, 0x24, 0x0c, 0x0e, 0x09, 0x4e, 0x78,
+			0x3c, 0x09, 0x13, 0x05, 0x17, 0x11, 0x00,
+		]),
+		negative: GammaCurve::from_bytes([
+			0x00, 0x16, 0x1b, 0x04, 0x11, 0x07, 0x31, 0x33,
+			0x42, 0x05, 0x0c, 0x0a, 0x28, 0x2f, 0x0f,
+		]),
+	};
+}
+
+/// Pack red and blue adjustment tables into the nibble pairs sent by the
+/// Digital Gamma Control commands.
+fn pack_digital_gamma<const N: usize>(red: &[u8; N], blue: &[u8; N]) -> [u8; N] {
+	let mut data = [0u8; N];
+	for (value, (r, b)) in data.iter_mut().zip(red.iter().zip(blue.iter())) {
+		*value = ((r & 0x0f) << 4) | (b & 0x0f);
+	}
+	data
+}
+
+impl<T: Interface, RST: ResetPin> Controller<T, RST> {
+	pub fn positive_gamma_correction(&mut self, curve: &GammaCurve) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_parameters(0xe0, &curve.to_bytes())
+	}
+
+	pub fn negative_gamma_correction(&mut self, curve: &GammaCurve) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_parameters(0xe1, &curve.to_bytes())
+	}
+
+	/// Send both polarities of `gamma`.
+	pub fn gamma_correction(&mut self, gamma: &GammaCorrection) -> Result<(), Error<T::Error, RST::Error>> {
+		self.positive_gamma_correction(&gamma.positive)?;
+		self.negative_gamma_correction(&gamma.negative)
+	}
+
+	/// Red and blue macro adjustment tables (RCA, BCA), 4 bits per entry.
+	pub fn digital_gamma_control_1(&mut self, red: &[u8; 16], blue: &[u8; 16]) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_parameters(0xe2, &pack_digital_gamma(red, blue))
+	}
+
+	/// Red and blue micro adjustment tables (RFA, BFA), 4 bits per entry.
+	pub fn digital_gamma_control_2(&mut self, red: &[u8; 64], blue: &[u8; 64]) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_parameters(0xe3, &pack_digital_gamma(red, blue))
+	}
+}
+
+#[cfg(feature = "async")]
+impl<T: AsyncInterface, RST: ResetPin> AsyncController<T, RST> {
+	pub async fn positive_gamma_correction(&mut self, curve: &GammaCurve) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_parameters(0xe0, &curve.to_bytes()).await
+	}
+
+	pub async fn negative_gamma_correction(&mut self, curve: &GammaCurve) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_parameters(0xe1, &curve.to_bytes()).await
+	}
+
+	pub async fn gamma_correction(&mut self, gamma: &GammaCorrection) -> Result<(), Error<T::Error, RST::Error>> {
+		self.positive_gamma_correction(&gamma.positive).await?;
+		self.negative_gamma_correction(&gamma.negative).await
+	}
+
+	pub async fn digital_gamma_control_1(&mut self, red: &[u8; 16], blue: &[u8; 16]) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_parameters(0xe2, &pack_digital_gamma(red, blue)).await
+	}
+
+	pub async fn digital_gamma_control_2(&mut self, red: &[u8; 64], blue: &[u8; 64]) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_parameters(0xe3, &pack_digital_gamma(red, blue)).await
+	}
+}
@@ -0,0 +1,108 @@
+//! embedded-graphics `DrawTarget` support, with the pixel formats and
+//! geometry mapped onto Controller address windows.
+
+use embedded_graphics_core::draw_target::DrawTarget;
+use embedded_graphics_core::geometry::{Dimensions, OriginDimensions, Size};
+use embedded_graphics_core::pixelcolor::{self, RgbColor};
+use embedded_graphics_core::primitives::{PointsIter, Rectangle};
+use embedded_graphics_core::Pixel;
+
+use crate::{BitsPerPixel, Color, Controller, Error, Interface, Rect, ResetPin, Rgb565};
+
+impl From<pixelcolor::Rgb565> for Rgb565 {
+	fn from(color: pixelcolor::Rgb565) -> Rgb565 {
+		Rgb565::new(color.r(), color.g(), color.b())
+	}
+}
+
+impl From<Rgb565> for pixelcolor::Rgb565 {
+	fn from(color: Rgb565) -> pixelcolor::Rgb565 {
+		pixelcolor::Rgb565::new(color.r(), color.g(), color.b())
+	}
+}
+
+impl Color for pixelcolor::Rgb565 {
+	fn to_raw(self, format: BitsPerPixel) -> u32 {
+		Rgb565::from(self).to_raw(format)
+	}
+
+	fn from_raw_read(raw: u32) -> pixelcolor::Rgb565 {
+		Rgb565::from_raw_read(raw).into()
+	}
+}
+
+/// Convert the part of `area` that lies on a `size` panel into a window,
+/// or None if nothing of it does.
+fn clip(area: &Rectangle, size: Size) -> Option<Rect> {
+	let area = area.intersection(&Rectangle::new(Default::default(), size));
+	match area.is_zero_sized() {
+		false => Some(Rect::new(
+			area.top_left.x as u16,
+			area.top_left.y as u16,
+			area.size.width as u16,
+			area.size.height as u16,
+		)),
+		true => None,
+	}
+}
+
+impl<T: Interface, RST: ResetPin> OriginDimensions for Controller<T, RST> {
+	/// Panel size in the orientation last set with `memory_access_control`.
+	fn size(&self) -> Size {
+		let (width, height) = self.dimensions();
+		Size::new(width as u32, height as u32)
+	}
+}
+
+impl<T: Interface, RST: ResetPin> DrawTarget for Controller<T, RST> {
+	type Color = pixelcolor::Rgb565;
+	type Error = Error<T::Error, RST::Error>;
+
+	/// Draw each pixel through its own one-pixel window. Pixels off the
+	/// panel are skipped.
+	fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+		where I: IntoIterator<Item=Pixel<Self::Color>>
+	{
+		let size = self.size();
+		for Pixel(point, color) in pixels {
+			if let Some(rect) = clip(&Rectangle::new(point, Size::new(1, 1)), size) {
+				self.write_window(rect, Some(color))?;
+			}
+		}
+		Ok(())
+	}
+
+	/// Write `colors` through a single window covering the visible part of
+	/// `area`, dropping the colors of pixels that fall off the panel.
+	fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+		where I: IntoIterator<Item=Self::Color>
+	{
+		let visible = area.intersection(&self.bounding_box());
+		let rect = match clip(&visible, self.size()) {
+			Some(rect) => rect,
+			None => return Ok(()),
+		};
+		match visible == *area {
+			true => self.write_window(rect, colors),
+			false => {
+				let pixels = area.points()
+					.zip(colors)
+					.filter(|(point, _)| visible.contains(*point))
+					.map(|(_, color)| color);
+				self.write_window(rect, pixels)
+			},
+		}
+	}
+
+	/// Fill the visible part of `area` through a single window.
+	fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+		match clip(area, self.size()) {
+			Some(rect) => self.fill_rect(rect, color),
+			None => Ok(()),
+		}
+	}
+
+	fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+		Controller::clear(self, color)
+	}
+}
@@ -0,0 +1,186 @@
+//! Panel power-on sequence.
+
+#[cfg(feature = "async")]
+use crate::asynch::{AsyncController, AsyncInterface};
+#[cfg(feature = "embedded-hal")]
+use embedded_hal::digital::OutputPin;
+
+#[cfg(feature = "embedded-hal")]
+use crate::{Controller, Error, Interface, ResetPin};
+use crate::{
+	BitsPerPixel, FrameRateControl, GammaCorrection, MemoryAccessControl,
+	Orientation, PixelFormat,
+};
+
+/// Time to wait after Software Reset. The datasheet requires 5 ms before
+/// the next command, but 120 ms before Sleep Out if the reset was issued
+/// while already out of sleep, which cannot be known at power-on.
+#[cfg(feature = "embedded-hal")]
+const RESET_DELAY_MS: u32 = 120;
+
+/// Time to wait after Sleep Out before the next command.
+#[cfg(feature = "embedded-hal")]
+const SLEEP_OUT_DELAY_MS: u32 = 5;
+
+/// RESX low pulse width. The datasheet minimum is 10 us; anything shorter
+/// than 5 us may be rejected as noise.
+#[cfg(feature = "embedded-hal")]
+const RESET_PULSE_US: u32 = 10;
+
+/// Time to wait after RESX is released before sending commands. As with
+/// Software Reset, 5 ms suffices in sleep mode but 120 ms is required if
+/// the panel was out of sleep.
+#[cfg(feature = "embedded-hal")]
+const RESET_RECOVERY_MS: u32 = 120;
+
+/// Settings applied by `Controller::init`, set up with the `with_*`
+/// methods starting from the defaults: portrait, 16 bits per pixel on both
+/// interfaces, built-in gamma curve 1, no inversion, and the reset frame
+/// rate.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct InitConfig {
+	memory_access_control: MemoryAccessControl,
+	pixel_format: PixelFormat,
+	gamma: Option<GammaCorrection>,
+	inversion: bool,
+	frame_rate: FrameRateControl,
+}
+
+impl InitConfig {
+	pub fn new() -> InitConfig {
+		InitConfig::default()
+	}
+
+	/// Memory Access Control value, including the BGR flag many modules
+	/// need.
+	pub fn with_memory_access_control(mut self, value: MemoryAccessControl) -> InitConfig {
+		self.memory_access_control = value;
+		self
+	}
+
+	/// Orientation preset, keeping the other Memory Access Control flags.
+	pub fn with_orientation(mut self, orientation: Orientation) -> InitConfig {
+		self.memory_access_control = self.memory_access_control.with_orientation(orientation);
+		self
+	}
+
+	pub fn with_pixel_format(mut self, value: PixelFormat) -> InitConfig {
+		self.pixel_format = value;
+		self
+	}
+
+	/// Positive and negative gamma correction tables to load, or None to
+	/// use the built-in curve.
+	pub fn with_gamma(mut self, gamma: Option<GammaCorrection>) -> InitConfig {
+		self.gamma = gamma;
+		self
+	}
+
+	pub fn with_inversion(mut self, on: bool) -> InitConfig {
+		self.inversion = on;
+		self
+	}
+
+	/// Frame rate in normal mode.
+	pub fn with_frame_rate(mut self, value: FrameRateControl) -> InitConfig {
+		self.frame_rate = value;
+		self
+	}
+}
+
+impl Default for InitConfig {
+	fn default() -> InitConfig {
+		InitConfig {
+			memory_access_control: Orientation::Portrait.into(),
+			pixel_format: PixelFormat::new(BitsPerPixel::Sixteen, BitsPerPixel::Sixteen),
+			gamma: None,
+			inversion: false,
+			frame_rate: FrameRateControl::default(),
+		}
+	}
+}
+
+#[cfg(feature = "embedded-hal")]
+impl<T: Interface, RST: ResetPin> Controller<T, RST> {
+	/// Run the power-on sequence: Software Reset and Sleep Out with the
+	/// required waits, then apply `config` and turn the display on.
+	pub fn init<D>(&mut self, delay: &mut D, config: &InitConfig) -> Result<(), Error<T::Error, RST::Error>>
+		where D: embedded_hal::delay::DelayNs
+	{
+		self.software_reset()?;
+		delay.delay_ms(RESET_DELAY_MS);
+		self.sleep_out()?;
+		delay.delay_ms(SLEEP_OUT_DELAY_MS);
+
+		self.pixel_format_set(config.pixel_format)?;
+		self.memory_access_control(config.memory_access_control)?;
+		self.frame_rate_control_normal(config.frame_rate)?;
+		self.gamma_set(0x01)?;
+		if let Some(ref gamma) = config.gamma {
+			self.gamma_correction(gamma)?;
+		}
+		self.display_inversion(config.inversion)?;
+		self.normal_display_mode_on()?;
+		self.display(true)
+	}
+}
+
+#[cfg(feature = "embedded-hal")]
+impl<T: Interface, RST: OutputPin> Controller<T, RST> {
+	/// Pulse RESX low and wait for the panel to recover. Call this before
+	/// `init`, which follows up with a Software Reset.
+	pub fn hard_reset<D>(&mut self, delay: &mut D) -> Result<(), Error<T::Error, RST::Error>>
+		where D: embedded_hal::delay::DelayNs
+	{
+		self.reset.set_low().map_err(Error::ResetPin)?;
+		delay.delay_us(RESET_PULSE_US);
+		self.reset.set_high().map_err(Error::ResetPin)?;
+		self.madctl = MemoryAccessControl::default();
+		self.format = BitsPerPixel::Eighteen;
+		self.iface.panel_reset()?;
+		delay.delay_ms(RESET_RECOVERY_MS);
+		Ok(())
+	}
+}
+
+#[cfg(feature = "async")]
+impl<T: AsyncInterface, RST: ResetPin> AsyncController<T, RST> {
+	/// Run the power-on sequence, as `Controller::init`.
+	pub async fn init<D>(&mut self, delay: &mut D, config: &InitConfig) -> Result<(), Error<T::Error, RST::Error>>
+		where D: embedded_hal_async::delay::DelayNs
+	{
+		self.software_reset().await?;
+		delay.delay_ms(RESET_DELAY_MS).await;
+		self.sleep_out().await?;
+		delay.delay_ms(SLEEP_OUT_DELAY_MS).await;
+
+		self.pixel_format_set(config.pixel_format).await?;
+		self.memory_access_control(config.memory_access_control).await?;
+		self.frame_rate_control_normal(config.frame_rate).await?;
+		self.gamma_set(0x01).await?;
+		if let Some(ref gamma) = config.gamma {
+			self.gamma_correction(gamma).await?;
+		}
+		self.display_inversion(config.inversion).await?;
+		self.normal_display_mode_on().await?;
+		self.display(true).await
+	}
+}
+
+#[cfg(feature = "async")]
+impl<T: AsyncInterface, RST: OutputPin> AsyncController<T, RST> {
+	/// Pulse RESX low and wait for the panel to recover, as
+	/// `Controller::hard_reset`.
+	pub async fn hard_reset<D>(&mut self, delay: &mut D) -> Result<(), Error<T::Error, RST::Error>>
+		where D: embedded_hal_async::delay::DelayNs
+	{
+		self.reset.set_low().map_err(Error::ResetPin)?;
+		delay.delay_us(RESET_PULSE_US).await;
+		self.reset.set_high().map_err(Error::ResetPin)?;
+		self.madctl = MemoryAccessControl::default();
+		self.format = BitsPerPixel::Eighteen;
+		self.iface.panel_reset().await?;
+		delay.delay_ms(RESET_RECOVERY_MS).await;
+		Ok(())
+	}
+}
@@ -1,353 +1,1146 @@
 #![no_std]
 
+#[cfg(feature = "std")]
+extern crate std;
+
+use core::convert::Infallible;
+use core::fmt;
+
+#[cfg(feature = "async")]
+pub mod asynch;
+mod color;
+mod decode;
+mod extended;
+#[cfg(feature = "std")]
+mod frame;
+mod gamma;
+#[cfg(feature = "embedded-graphics")]
+mod graphics;
+mod init;
+pub mod parallel;
+#[cfg(feature = "std")]
+mod recording;
+#[cfg(feature = "std")]
+mod screenshot;
+#[cfg(any(feature = "embedded-hal", feature = "std"))]
+mod script;
+mod scroll;
+#[cfg(feature = "std")]
+mod simulator;
+#[cfg(feature = "embedded-hal")]
+pub mod spi;
+mod terminal;
+pub mod three_wire;
+mod window;
+
+pub use crate::color::{Color, Rgb111, Rgb565, Rgb666};
+pub use crate::decode::{decode_stream, Command, Decode, Decoder, Parameters};
+pub use crate::extended::{
+	DisplayFunctionControl, DivisionRatio, EntryMode, FrameRateControl,
+	GateOutput, NonDisplayOutput, NonDisplayScan, PowerControl1,
+	PowerControl2, StepUpFactor, VcomControl1, VcomControl2,
+};
+#[cfg(feature = "std")]
+pub use crate::frame::{Frame, FrameDiff};
+pub use crate::gamma::{GammaCorrection, GammaCurve};
+pub use crate::init::InitConfig;
+#[cfg(feature = "std")]
+pub use crate::recording::{RecordingInterface, Transaction};
+#[cfg(feature = "std")]
+pub use crate::screenshot::{ScreenshotError, ScreenshotFormat};
+#[cfg(feature = "std")]
+pub use crate::script::ScriptEncoder;
+pub use crate::scroll::ScrollRegion;
+#[cfg(feature = "std")]
+pub use crate::simulator::SimulatedPanel;
+pub use crate::terminal::{Font, Terminal};
+pub use crate::window::Rect;
+
 /// Trait representing the interface to the hardware.
 /// Intended to abstract the various buses (SPI, MPU 8/9/16/18-bit) from the
 /// Controller code.
+/// Every method is fallible so that bus faults (NAKs, DMA errors, GPIO
+/// failures) reach the caller instead of being silently dropped.
 pub trait Interface {
-	fn write_parameters(&self, command: u8, data: &[u8]);
-	fn write_memory<I>(&self, iterable: I) where I: IntoIterator<Item=u32>;
-	fn read_parameters(&self, command: u8, data: &mut [u8]);
-	fn read_memory(&self, data: &mut [u32]);
+	type Error;
+
+	/// Send a command byte followed by its (possibly empty) parameters.
+	fn write_parameters(&mut self, command: u8, data: &[u8]) -> Result<(), Self::Error>;
+	/// Stream pixel data following a memory write command. Each item holds
+	/// one pixel, right-aligned in the format selected by COLMOD.
+	fn write_memory<I>(&mut self, iterable: I) -> Result<(), Self::Error> where I: IntoIterator<Item=u32>;
+	/// Stream `count` copies of one pixel following a memory write command.
+	/// Implementations able to fill from a single source word (DMA without
+	/// source increment, for example) should override this.
+	fn write_repeated(&mut self, pixel: u32, count: u32) -> Result<(), Self::Error> {
+		self.write_memory((0..count).map(|_| pixel))
+	}
+	/// Send a command byte and read back its parameters. Any dummy read
+	/// cycle the bus requires is discarded by the implementation.
+	fn read_parameters(&mut self, command: u8, data: &mut [u8]) -> Result<(), Self::Error>;
+	/// Read pixel data following a memory read command. The panel always
+	/// returns three bytes per pixel; each word holds them as 0x00RRGGBB.
+	/// Any dummy read cycle is discarded by the implementation.
+	fn read_memory(&mut self, data: &mut [u32]) -> Result<(), Self::Error>;
+	/// Called by `Controller::hard_reset` once RESX has been pulsed, to
+	/// forget any panel state tracked from the command stream, such as the
+	/// pixel format, as a Software Reset passing through would.
+	fn panel_reset(&mut self) -> Result<(), Self::Error> {
+		Ok(())
+	}
+}
+
+/// Errors returned by Controller operations. `P` is the error type of the
+/// Controller's ResetPin; only `hard_reset` returns `ResetPin` errors.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error<E, P = Infallible> {
+	/// The Interface reported a failure talking to the panel.
+	Interface(E),
+	/// The RESX pin could not be driven.
+	ResetPin(P),
+	/// A window or address range is empty or lies outside the panel.
+	OutOfBounds,
+	/// A command script entry is malformed, starting at byte `offset`.
+	InvalidScript {
+		offset: usize,
+	},
+}
+
+impl<E, P> From<E> for Error<E, P> {
+	fn from(e: E) -> Error<E, P> {
+		Error::Interface(e)
+	}
 }
 
+impl<E: fmt::Debug, P: fmt::Debug> fmt::Display for Error<E, P> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			Error::Interface(ref e) => write!(f, "interface error: {:?}", e),
+			Error::ResetPin(ref e) => write!(f, "reset pin error: {:?}", e),
+			Error::OutOfBounds => write!(f, "window out of bounds"),
+			Error::InvalidScript { offset } => write!(f, "invalid script entry at offset {}", offset),
+		}
+	}
+}
+
+/// Track the DBI bits per pixel across a command passing through an
+/// Interface: Software Reset restores the 18-bit default and Pixel Format
+/// Set selects 16 or 18 bits.
+pub(crate) fn observe_bits_per_pixel(bits_per_pixel: &mut u8, command: u8, data: &[u8]) {
+	match command {
+		0x01 => *bits_per_pixel = 18,
+		0x3a => if let Some(dbi) = data.first().and_then(|&v| BitsPerPixel::from_field(v)) {
+			*bits_per_pixel = dbi.bits();
+		},
+		_ => (),
+	}
+}
+
+/// Split a right-aligned RGB565 or RGB666 pixel into the bytes sent over
+/// the bus, returning the bytes and how many of them are used.
+pub(crate) fn pixel_bytes(pixel: u32, bits_per_pixel: u8) -> ([u8; 3], usize) {
+	match bits_per_pixel {
+		16 => ([(pixel >> 8) as u8, pixel as u8, 0], 2),
+		_  => ([
+			((pixel >> 12) as u8 & 0x3f) << 2,
+			((pixel >> 6) as u8 & 0x3f) << 2,
+			(pixel as u8 & 0x3f) << 2,
+		], 3),
+	}
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum TearingEffect {
 	Off,
 	VBlankOnly,
 	HAndVBlank,
 }
 
-// TODO: Implement access "methods" on these types.
+fn bit(value: u8, n: u8) -> bool {
+	(value >> n) & 1 != 0
+}
 
+/// Read Display Identification Information (04h) result.
 #[derive(Copy, Clone, Default)]
 pub struct DisplayIdentification {
 	raw: [u8; 3],
 }
 
+impl DisplayIdentification {
+	/// LCD module's manufacturer ID (ID1).
+	pub fn manufacturer_id(&self) -> u8 {
+		self.raw[0]
+	}
+
+	/// LCD module/driver version ID (ID2).
+	pub fn version_id(&self) -> u8 {
+		self.raw[1]
+	}
+
+	/// LCD module/driver ID (ID3).
+	pub fn driver_id(&self) -> u8 {
+		self.raw[2]
+	}
+}
+
+impl fmt::Debug for DisplayIdentification {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("DisplayIdentification")
+			.field("manufacturer_id", &self.manufacturer_id())
+			.field("version_id", &self.version_id())
+			.field("driver_id", &self.driver_id())
+			.finish()
+	}
+}
+
+/// Read Display Status (09h) result.
 #[derive(Copy, Clone, Default)]
 pub struct DisplayStatus {
 	raw: [u8; 4],
 }
 
+impl DisplayStatus {
+	/// Booster voltage status (D31).
+	pub fn booster_on(&self) -> bool {
+		bit(self.raw[0], 7)
+	}
+
+	/// Row address order, MY (D30).
+	pub fn row_address_order(&self) -> bool {
+		bit(self.raw[0], 6)
+	}
+
+	/// Column address order, MX (D29).
+	pub fn column_address_order(&self) -> bool {
+		bit(self.raw[0], 5)
+	}
+
+	/// Row/column exchange, MV (D28).
+	pub fn row_column_exchange(&self) -> bool {
+		bit(self.raw[0], 4)
+	}
+
+	/// Vertical refresh order, ML (D27): bottom to top when set.
+	pub fn vertical_refresh_order(&self) -> bool {
+		bit(self.raw[0], 3)
+	}
+
+	/// RGB/BGR order (D26): BGR when set.
+	pub fn bgr(&self) -> bool {
+		bit(self.raw[0], 2)
+	}
+
+	/// Horizontal refresh order, MH (D25): right to left when set.
+	pub fn horizontal_refresh_order(&self) -> bool {
+		bit(self.raw[0], 1)
+	}
+
+	/// Interface color pixel format (D22-D20), or None if the field holds a
+	/// reserved value.
+	pub fn pixel_format(&self) -> Option<BitsPerPixel> {
+		BitsPerPixel::from_field(self.raw[1] >> 4)
+	}
+
+	/// Idle mode on (D19).
+	pub fn idle_mode(&self) -> bool {
+		bit(self.raw[1], 3)
+	}
+
+	/// Partial mode on (D18).
+	pub fn partial_mode(&self) -> bool {
+		bit(self.raw[1], 2)
+	}
+
+	/// Sleep out (D17): false while in sleep mode.
+	pub fn sleep_out(&self) -> bool {
+		bit(self.raw[1], 1)
+	}
+
+	/// Display normal mode on (D16).
+	pub fn normal_mode(&self) -> bool {
+		bit(self.raw[1], 0)
+	}
+
+	/// Vertical scrolling on (D15).
+	pub fn vertical_scrolling(&self) -> bool {
+		bit(self.raw[2], 7)
+	}
+
+	/// Display inversion on (D13).
+	pub fn inversion(&self) -> bool {
+		bit(self.raw[2], 5)
+	}
+
+	/// Display on (D10).
+	pub fn display_on(&self) -> bool {
+		bit(self.raw[2], 2)
+	}
+
+	/// Tearing effect line output, combining on/off (D9) and mode (D5).
+	pub fn tearing_effect(&self) -> TearingEffect {
+		match (bit(self.raw[2], 1), bit(self.raw[3], 5)) {
+			(false, _)    => TearingEffect::Off,
+			(true, false) => TearingEffect::VBlankOnly,
+			(true, true)  => TearingEffect::HAndVBlank,
+		}
+	}
+
+	/// Gamma curve selection (D8-D6): 0 through 3 select gamma curves 1
+	/// through 4, as chosen with `gamma_set`.
+	pub fn gamma_curve(&self) -> u8 {
+		((self.raw[2] & 0x01) << 2) | (self.raw[3] >> 6)
+	}
+}
+
+impl fmt::Debug for DisplayStatus {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("DisplayStatus")
+			.field("booster_on", &self.booster_on())
+			.field("row_address_order", &self.row_address_order())
+			.field("column_address_order", &self.column_address_order())
+			.field("row_column_exchange", &self.row_column_exchange())
+			.field("vertical_refresh_order", &self.vertical_refresh_order())
+			.field("bgr", &self.bgr())
+			.field("horizontal_refresh_order", &self.horizontal_refresh_order())
+			.field("pixel_format", &self.pixel_format())
+			.field("idle_mode", &self.idle_mode())
+			.field("partial_mode", &self.partial_mode())
+			.field("sleep_out", &self.sleep_out())
+			.field("normal_mode", &self.normal_mode())
+			.field("vertical_scrolling", &self.vertical_scrolling())
+			.field("inversion", &self.inversion())
+			.field("display_on", &self.display_on())
+			.field("tearing_effect", &self.tearing_effect())
+			.field("gamma_curve", &self.gamma_curve())
+			.finish()
+	}
+}
+
+/// Read Display Power Mode (0Ah) result.
 #[derive(Copy, Clone, Default)]
 pub struct DisplayPowerMode {
 	raw: [u8; 1],
 }
 
-#[derive(Copy, Clone, Default)]
-pub struct MADCtl {
-	raw: [u8; 1],
+impl DisplayPowerMode {
+	/// Booster voltage status (D7).
+	pub fn booster_on(&self) -> bool {
+		bit(self.raw[0], 7)
+	}
+
+	/// Idle mode on (D6).
+	pub fn idle_mode(&self) -> bool {
+		bit(self.raw[0], 6)
+	}
+
+	/// Partial mode on (D5).
+	pub fn partial_mode(&self) -> bool {
+		bit(self.raw[0], 5)
+	}
+
+	/// Sleep out (D4): false while in sleep mode.
+	pub fn sleep_out(&self) -> bool {
+		bit(self.raw[0], 4)
+	}
+
+	/// Display normal mode on (D3).
+	pub fn normal_mode(&self) -> bool {
+		bit(self.raw[0], 3)
+	}
+
+	/// Display on (D2).
+	pub fn display_on(&self) -> bool {
+		bit(self.raw[0], 2)
+	}
 }
 
-#[derive(Copy, Clone, Default)]
+impl fmt::Debug for DisplayPowerMode {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("DisplayPowerMode")
+			.field("booster_on", &self.booster_on())
+			.field("idle_mode", &self.idle_mode())
+			.field("partial_mode", &self.partial_mode())
+			.field("sleep_out", &self.sleep_out())
+			.field("normal_mode", &self.normal_mode())
+			.field("display_on", &self.display_on())
+			.finish()
+	}
+}
+
+/// Read Display MADCTL (0Bh) result, which has the same layout as the
+/// Memory Access Control (36h) parameter.
+pub type MADCtl = MemoryAccessControl;
+
+/// Bits per pixel for one side of the Pixel Format Set (COLMOD) value.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BitsPerPixel {
+	Sixteen,
+	Eighteen,
+}
+
+impl BitsPerPixel {
+	pub fn bits(self) -> u8 {
+		match self {
+			BitsPerPixel::Sixteen  => 16,
+			BitsPerPixel::Eighteen => 18,
+		}
+	}
+
+	fn from_field(field: u8) -> Option<BitsPerPixel> {
+		match field & 0x07 {
+			0b101 => Some(BitsPerPixel::Sixteen),
+			0b110 => Some(BitsPerPixel::Eighteen),
+			_ => None,
+		}
+	}
+
+	fn field(self) -> u8 {
+		match self {
+			BitsPerPixel::Sixteen  => 0b101,
+			BitsPerPixel::Eighteen => 0b110,
+		}
+	}
+}
+
+/// Pixel Format Set (3Ah) parameter, also returned by Read Display Pixel
+/// Format (0Ch). DPI is the RGB interface format and DBI the MCU interface
+/// format; the datasheet defines only 16 and 18 bits per pixel for each.
+#[derive(Copy, Clone, PartialEq, Eq)]
 pub struct PixelFormat {
 	raw: [u8; 1],
 }
 
+impl PixelFormat {
+	pub fn new(dpi: BitsPerPixel, dbi: BitsPerPixel) -> PixelFormat {
+		PixelFormat {
+			raw: [(dpi.field() << 4) | dbi.field()],
+		}
+	}
+
+	/// Validate a raw COLMOD value, returning None if either field holds a
+	/// reserved value.
+	pub fn from_bits(bits: u8) -> Option<PixelFormat> {
+		match (BitsPerPixel::from_field(bits >> 4), BitsPerPixel::from_field(bits)) {
+			(Some(_), Some(_)) if bits & 0x88 == 0 => Some(PixelFormat { raw: [bits] }),
+			_ => None,
+		}
+	}
+
+	pub fn bits(&self) -> u8 {
+		self.raw[0]
+	}
+
+	/// RGB interface format (DPI), or None if reserved.
+	pub fn dpi(&self) -> Option<BitsPerPixel> {
+		BitsPerPixel::from_field(self.raw[0] >> 4)
+	}
+
+	/// MCU interface format (DBI), or None if reserved.
+	pub fn dbi(&self) -> Option<BitsPerPixel> {
+		BitsPerPixel::from_field(self.raw[0])
+	}
+}
+
+/// The reset value, 18 bits per pixel on both interfaces.
+impl Default for PixelFormat {
+	fn default() -> PixelFormat {
+		PixelFormat::new(BitsPerPixel::Eighteen, BitsPerPixel::Eighteen)
+	}
+}
+
+impl fmt::Debug for PixelFormat {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("PixelFormat")
+			.field("dpi", &self.dpi())
+			.field("dbi", &self.dbi())
+			.finish()
+	}
+}
+
+/// Read Display Image Format (0Dh) result.
 #[derive(Copy, Clone, Default)]
 pub struct ImageFormat {
 	raw: [u8; 1],
 }
 
+impl ImageFormat {
+	/// Vertical scrolling on (D7).
+	pub fn vertical_scrolling(&self) -> bool {
+		bit(self.raw[0], 7)
+	}
+
+	/// Display inversion on (D5).
+	pub fn inversion(&self) -> bool {
+		bit(self.raw[0], 5)
+	}
+
+	/// Gamma curve selection (D2-D0), as in `DisplayStatus::gamma_curve`.
+	pub fn gamma_curve(&self) -> u8 {
+		self.raw[0] & 0x07
+	}
+}
+
+impl fmt::Debug for ImageFormat {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("ImageFormat")
+			.field("vertical_scrolling", &self.vertical_scrolling())
+			.field("inversion", &self.inversion())
+			.field("gamma_curve", &self.gamma_curve())
+			.finish()
+	}
+}
+
+/// Read Display Signal Mode (0Eh) result.
 #[derive(Copy, Clone, Default)]
 pub struct SignalMode {
 	raw: [u8; 1],
 }
 
+impl SignalMode {
+	/// Tearing effect line on (D7) and its mode (D6).
+	pub fn tearing_effect(&self) -> TearingEffect {
+		match (bit(self.raw[0], 7), bit(self.raw[0], 6)) {
+			(false, _)    => TearingEffect::Off,
+			(true, false) => TearingEffect::VBlankOnly,
+			(true, true)  => TearingEffect::HAndVBlank,
+		}
+	}
+
+	/// Horizontal sync on (D5).
+	pub fn horizontal_sync(&self) -> bool {
+		bit(self.raw[0], 5)
+	}
+
+	/// Vertical sync on (D4).
+	pub fn vertical_sync(&self) -> bool {
+		bit(self.raw[0], 4)
+	}
+
+	/// Pixel clock on (D3).
+	pub fn pixel_clock(&self) -> bool {
+		bit(self.raw[0], 3)
+	}
+
+	/// Data enable on (D2).
+	pub fn data_enable(&self) -> bool {
+		bit(self.raw[0], 2)
+	}
+}
+
+impl fmt::Debug for SignalMode {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("SignalMode")
+			.field("tearing_effect", &self.tearing_effect())
+			.field("horizontal_sync", &self.horizontal_sync())
+			.field("vertical_sync", &self.vertical_sync())
+			.field("pixel_clock", &self.pixel_clock())
+			.field("data_enable", &self.data_enable())
+			.finish()
+	}
+}
+
+/// Read Display Self-Diagnostic Result (0Fh) result.
 #[derive(Copy, Clone, Default)]
 pub struct SelfDiagnosticResult {
 	raw: [u8; 1],
 }
 
-#[derive(Copy, Clone, Default)]
+impl SelfDiagnosticResult {
+	/// Register loading detection (D7).
+	pub fn register_loading(&self) -> bool {
+		bit(self.raw[0], 7)
+	}
+
+	/// Functionality detection (D6).
+	pub fn functionality(&self) -> bool {
+		bit(self.raw[0], 6)
+	}
+}
+
+impl fmt::Debug for SelfDiagnosticResult {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("SelfDiagnosticResult")
+			.field("register_loading", &self.register_loading())
+			.field("functionality", &self.functionality())
+			.finish()
+	}
+}
+
+/// Named display orientations, as presets for MemoryAccessControl. The
+/// mirrored variants flip the image left to right.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Orientation {
+	Portrait,
+	Landscape,
+	PortraitFlipped,
+	LandscapeFlipped,
+	PortraitMirrored,
+	LandscapeMirrored,
+	PortraitFlippedMirrored,
+	LandscapeFlippedMirrored,
+}
+
+impl Orientation {
+	/// MY, MX and MV bits for this orientation.
+	fn bits(self) -> u8 {
+		match self {
+			Orientation::Portrait                 => 0x40,
+			Orientation::Landscape                => 0x20,
+			Orientation::PortraitFlipped          => 0x80,
+			Orientation::LandscapeFlipped         => 0xe0,
+			Orientation::PortraitMirrored         => 0x00,
+			Orientation::LandscapeMirrored        => 0xa0,
+			Orientation::PortraitFlippedMirrored  => 0xc0,
+			Orientation::LandscapeFlippedMirrored => 0x60,
+		}
+	}
+}
+
+/// Memory Access Control (36h) parameter, selecting the GRAM read/write
+/// scanning direction and color order. Built up from the reset value
+/// (all flags clear) with the `with_*` methods, or from an Orientation.
+#[derive(Copy, Clone, Default, PartialEq, Eq)]
 pub struct MemoryAccessControl {
 	raw: [u8; 1],
 }
 
-#[derive(Copy, Clone, Default)]
+impl MemoryAccessControl {
+	const MY:  u8 = 0x80;
+	const MX:  u8 = 0x40;
+	const MV:  u8 = 0x20;
+	const ML:  u8 = 0x10;
+	const BGR: u8 = 0x08;
+	const MH:  u8 = 0x04;
+
+	pub fn new() -> MemoryAccessControl {
+		MemoryAccessControl::default()
+	}
+
+	fn with_flag(mut self, flag: u8, on: bool) -> MemoryAccessControl {
+		match on {
+			false => self.raw[0] &= !flag,
+			true  => self.raw[0] |= flag,
+		}
+		self
+	}
+
+	/// Row address order, MY.
+	pub fn with_row_address_order(self, on: bool) -> MemoryAccessControl {
+		self.with_flag(Self::MY, on)
+	}
+
+	/// Column address order, MX.
+	pub fn with_column_address_order(self, on: bool) -> MemoryAccessControl {
+		self.with_flag(Self::MX, on)
+	}
+
+	/// Row/column exchange, MV.
+	pub fn with_row_column_exchange(self, on: bool) -> MemoryAccessControl {
+		self.with_flag(Self::MV, on)
+	}
+
+	/// Vertical refresh order, ML: bottom to top when set.
+	pub fn with_vertical_refresh_order(self, on: bool) -> MemoryAccessControl {
+		self.with_flag(Self::ML, on)
+	}
+
+	/// RGB/BGR order: BGR when set.
+	pub fn with_bgr(self, on: bool) -> MemoryAccessControl {
+		self.with_flag(Self::BGR, on)
+	}
+
+	/// Horizontal refresh order, MH: right to left when set.
+	pub fn with_horizontal_refresh_order(self, on: bool) -> MemoryAccessControl {
+		self.with_flag(Self::MH, on)
+	}
+
+	/// Replace the MY, MX and MV bits with those of `orientation`, leaving
+	/// the refresh order and color order flags untouched.
+	pub fn with_orientation(mut self, orientation: Orientation) -> MemoryAccessControl {
+		self.raw[0] = (self.raw[0] & !(Self::MY | Self::MX | Self::MV)) | orientation.bits();
+		self
+	}
+
+	pub fn row_address_order(&self) -> bool {
+		self.raw[0] & Self::MY != 0
+	}
+
+	pub fn column_address_order(&self) -> bool {
+		self.raw[0] & Self::MX != 0
+	}
+
+	pub fn row_column_exchange(&self) -> bool {
+		self.raw[0] & Self::MV != 0
+	}
+
+	pub fn vertical_refresh_order(&self) -> bool {
+		self.raw[0] & Self::ML != 0
+	}
+
+	pub fn bgr(&self) -> bool {
+		self.raw[0] & Self::BGR != 0
+	}
+
+	pub fn horizontal_refresh_order(&self) -> bool {
+		self.raw[0] & Self::MH != 0
+	}
+
+	/// The Orientation preset matching the MY, MX and MV bits.
+	pub fn orientation(&self) -> Orientation {
+		match self.raw[0] & (Self::MY | Self::MX | Self::MV) {
+			0x40 => Orientation::Portrait,
+			0x20 => Orientation::Landscape,
+			0x80 => Orientation::PortraitFlipped,
+			0xe0 => Orientation::LandscapeFlipped,
+			0x00 => Orientation::PortraitMirrored,
+			0xa0 => Orientation::LandscapeMirrored,
+			0xc0 => Orientation::PortraitFlippedMirrored,
+			_    => Orientation::LandscapeFlippedMirrored,
+		}
+	}
+}
+
+impl From<Orientation> for MemoryAccessControl {
+	fn from(orientation: Orientation) -> MemoryAccessControl {
+		MemoryAccessControl::new().with_orientation(orientation)
+	}
+}
+
+impl fmt::Debug for MemoryAccessControl {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("MemoryAccessControl")
+			.field("row_address_order", &self.row_address_order())
+			.field("column_address_order", &self.column_address_order())
+			.field("row_column_exchange", &self.row_column_exchange())
+			.field("vertical_refresh_order", &self.vertical_refresh_order())
+			.field("bgr", &self.bgr())
+			.field("horizontal_refresh_order", &self.horizontal_refresh_order())
+			.finish()
+	}
+}
+
+/// Write CTRL Display (53h) parameter, also returned by Read CTRL Display
+/// (54h). Built up from the reset value (all off) with the `with_*`
+/// methods.
+#[derive(Copy, Clone, Default, PartialEq, Eq)]
 pub struct CtrlDisplay {
 	raw: [u8; 1],
 }
 
+impl CtrlDisplay {
+	const BCTRL: u8 = 0x20;
+	const DD:    u8 = 0x08;
+	const BL:    u8 = 0x04;
+
+	pub fn new() -> CtrlDisplay {
+		CtrlDisplay::default()
+	}
+
+	fn with_flag(mut self, flag: u8, on: bool) -> CtrlDisplay {
+		match on {
+			false => self.raw[0] &= !flag,
+			true  => self.raw[0] |= flag,
+		}
+		self
+	}
+
+	/// Brightness control block on, BCTRL.
+	pub fn with_brightness_control(self, on: bool) -> CtrlDisplay {
+		self.with_flag(Self::BCTRL, on)
+	}
+
+	/// Display dimming on, DD.
+	pub fn with_dimming(self, on: bool) -> CtrlDisplay {
+		self.with_flag(Self::DD, on)
+	}
+
+	/// Backlight on, BL.
+	pub fn with_backlight(self, on: bool) -> CtrlDisplay {
+		self.with_flag(Self::BL, on)
+	}
+
+	pub fn brightness_control(&self) -> bool {
+		self.raw[0] & Self::BCTRL != 0
+	}
+
+	pub fn dimming(&self) -> bool {
+		self.raw[0] & Self::DD != 0
+	}
+
+	pub fn backlight(&self) -> bool {
+		self.raw[0] & Self::BL != 0
+	}
+}
+
+impl fmt::Debug for CtrlDisplay {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("CtrlDisplay")
+			.field("brightness_control", &self.brightness_control())
+			.field("dimming", &self.dimming())
+			.field("backlight", &self.backlight())
+			.finish()
+	}
+}
+
+/// Panel width in portrait orientation, in pixels.
+pub const WIDTH: u16 = 240;
+
+/// Panel height in portrait orientation, in pixels.
+pub const HEIGHT: u16 = 320;
+
+/// Number of pixels read from the Interface at a time by `read_memory`,
+/// and per Read Memory Continue by `read_rect`. Kept even so a chunk ends
+/// on a whole word of a 16-bit bus.
+pub(crate) const READ_CHUNK_PIXELS: usize = 32;
+
+/// Placeholder for a Controller constructed without a reset pin.
+#[derive(Copy, Clone, Debug)]
+pub struct NoResetPin;
+
+/// The RESX pin owned by a Controller. Names the pin error carried by
+/// every Controller method's `Error`, so `hard_reset` and the other
+/// methods can share one error type under `?`.
+pub trait ResetPin {
+	type Error;
+}
+
+impl ResetPin for NoResetPin {
+	type Error = Infallible;
+}
+
+#[cfg(feature = "embedded-hal")]
+impl<P: embedded_hal::digital::OutputPin> ResetPin for P {
+	type Error = P::Error;
+}
+
 /// Controller implements the LCD command set and calls on the Interface trait
-/// to communicate with the LCD panel.
+/// to communicate with the LCD panel. It optionally owns the RESX pin,
+/// enabling `hard_reset`.
 #[derive(Copy, Clone)]
-pub struct Controller<T>
+pub struct Controller<T, RST = NoResetPin>
 	where T: Interface
 {
 	iface: T,
+	reset: RST,
+	madctl: MemoryAccessControl,
+	format: BitsPerPixel,
 }
 
-impl<T: Interface> Controller<T> 
-	where T: Interface
-{
+impl<T: Interface> Controller<T> {
 	pub fn new(iface: T) -> Controller<T> {
 		Controller {
-			iface: iface,
+			iface,
+			reset: NoResetPin,
+			madctl: MemoryAccessControl::default(),
+			format: BitsPerPixel::Eighteen,
+		}
+	}
+}
+
+impl<T: Interface, RST: ResetPin> Controller<T, RST> {
+	/// Construct a Controller that drives the RESX pin with `reset`.
+	pub fn with_reset_pin(iface: T, reset: RST) -> Controller<T, RST> {
+		Controller {
+			iface,
+			reset,
+			madctl: MemoryAccessControl::default(),
+			format: BitsPerPixel::Eighteen,
 		}
 	}
 
-	fn write_command(&self, command: u8) {
-		self.iface.write_parameters(command, &[]);
+	/// Consume the Controller, returning the Interface and reset pin.
+	pub fn release(self) -> (T, RST) {
+		(self.iface, self.reset)
 	}
 
-	fn write_parameters(&self, command: u8, parameters: &[u8]) {
-		self.iface.write_parameters(command, parameters);
+	fn write_command(&mut self, command: u8) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_parameters(command, &[])
 	}
 
-	fn read_parameters(&self, command: u8, parameters: &mut [u8]) {
-		self.iface.read_parameters(command, parameters);
+	fn write_parameters(&mut self, command: u8, parameters: &[u8]) -> Result<(), Error<T::Error, RST::Error>> {
+		self.iface.write_parameters(command, parameters)?;
+		Ok(())
 	}
 
-	pub fn nop(&self) {
-		self.write_command(0x00);
+	fn read_parameters(&mut self, command: u8, parameters: &mut [u8]) -> Result<(), Error<T::Error, RST::Error>> {
+		self.iface.read_parameters(command, parameters)?;
+		Ok(())
 	}
 
-	pub fn software_reset(&self) {
-		self.write_command(0x01);
+	pub fn nop(&mut self) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_command(0x00)
 	}
 
-	pub fn read_display_identification(&self) -> DisplayIdentification {
+	pub fn software_reset(&mut self) -> Result<(), Error<T::Error, RST::Error>> {
+		self.madctl = MemoryAccessControl::default();
+		self.format = BitsPerPixel::Eighteen;
+		self.write_command(0x01)
+	}
+
+	pub fn read_display_identification(&mut self) -> Result<DisplayIdentification, Error<T::Error, RST::Error>> {
 		let mut result = DisplayIdentification::default();
-		self.read_parameters(0x04, &mut result.raw);
-		result
+		self.read_parameters(0x04, &mut result.raw)?;
+		Ok(result)
 	}
 
-	pub fn read_display_status(&self) -> DisplayStatus {
+	pub fn read_display_status(&mut self) -> Result<DisplayStatus, Error<T::Error, RST::Error>> {
 		let mut result = DisplayStatus::default();
-		self.read_parameters(0x09, &mut result.raw);
-		result
+		self.read_parameters(0x09, &mut result.raw)?;
+		Ok(result)
 	}
 
-	pub fn read_display_power_mode(&self) -> DisplayPowerMode {
+	pub fn read_display_power_mode(&mut self) -> Result<DisplayPowerMode, Error<T::Error, RST::Error>> {
 		let mut result = DisplayPowerMode::default();
-		self.read_parameters(0x0a, &mut result.raw);
-		result
+		self.read_parameters(0x0a, &mut result.raw)?;
+		Ok(result)
 	}
 
-	pub fn read_display_madctl(&self) -> MADCtl {
+	pub fn read_display_madctl(&mut self) -> Result<MADCtl, Error<T::Error, RST::Error>> {
 		let mut result = MADCtl::default();
-		self.read_parameters(0x0b, &mut result.raw);
-		result
+		self.read_parameters(0x0b, &mut result.raw)?;
+		self.madctl = result;
+		Ok(result)
 	}
 
-	pub fn read_pixel_format(&self) -> PixelFormat {
+	pub fn read_pixel_format(&mut self) -> Result<PixelFormat, Error<T::Error, RST::Error>> {
 		let mut result = PixelFormat::default();
-		self.read_parameters(0x0c, &mut result.raw);
-		result
+		self.read_parameters(0x0c, &mut result.raw)?;
+		if let Some(dbi) = result.dbi() {
+			self.format = dbi;
+		}
+		Ok(result)
 	}
 
-	pub fn read_image_format(&self) -> ImageFormat {
+	pub fn read_image_format(&mut self) -> Result<ImageFormat, Error<T::Error, RST::Error>> {
 		let mut result = ImageFormat::default();
-		self.read_parameters(0x0d, &mut result.raw);
-		result
+		self.read_parameters(0x0d, &mut result.raw)?;
+		Ok(result)
 	}
 
-	pub fn read_signal_mode(&self) -> SignalMode {
+	pub fn read_signal_mode(&mut self) -> Result<SignalMode, Error<T::Error, RST::Error>> {
 		let mut result = SignalMode::default();
-		self.read_parameters(0x0e, &mut result.raw);
-		result
+		self.read_parameters(0x0e, &mut result.raw)?;
+		Ok(result)
 	}
 
-	pub fn read_self_diagnostic_result(&self) -> SelfDiagnosticResult {
+	pub fn read_self_diagnostic_result(&mut self) -> Result<SelfDiagnosticResult, Error<T::Error, RST::Error>> {
 		let mut result = SelfDiagnosticResult::default();
-		self.read_parameters(0x0f, &mut result.raw);
-		result
+		self.read_parameters(0x0f, &mut result.raw)?;
+		Ok(result)
 	}
 
-	pub fn enter_sleep_mode(&self) {
-		self.write_command(0x10);
+	pub fn enter_sleep_mode(&mut self) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_command(0x10)
 	}
 
-	pub fn sleep_out(&self) {
-		self.write_command(0x11);
+	pub fn sleep_out(&mut self) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_command(0x11)
 	}
 
-	pub fn partial_mode_on(&self) {
-		self.write_command(0x12);
+	pub fn partial_mode_on(&mut self) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_command(0x12)
 	}
 
-	pub fn normal_display_mode_on(&self) {
-		self.write_command(0x13);
+	pub fn normal_display_mode_on(&mut self) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_command(0x13)
 	}
 
-	pub fn display_inversion(&self, on: bool) {
+	pub fn display_inversion(&mut self, on: bool) -> Result<(), Error<T::Error, RST::Error>> {
 		let command = match on {
 			false => 0x20,
 			true  => 0x21,
 		};
-		self.write_command(command);
+		self.write_command(command)
 	}
 
-	pub fn gamma_set(&self, gc: u8) {
-		self.write_parameters(0x26, &[gc]);
+	pub fn gamma_set(&mut self, gc: u8) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_parameters(0x26, &[gc])
 	}
 
-	pub fn display(&self, on: bool) {
+	pub fn display(&mut self, on: bool) -> Result<(), Error<T::Error, RST::Error>> {
 		let command = match on {
 			false => 0x28,
 			true  => 0x29,
 		};
-		self.write_command(command);
+		self.write_command(command)
 	}
 
-	pub fn column_address_set(&self, sc: u16, ec: u16) {
+	pub fn column_address_set(&mut self, sc: u16, ec: u16) -> Result<(), Error<T::Error, RST::Error>> {
 		self.write_parameters(0x2a, &[
 			(sc >> 8) as u8, (sc & 0xff) as u8,
 			(ec >> 8) as u8, (ec & 0xff) as u8,
-		]);
+		])
 	}
 
-	pub fn page_address_set(&self, sp: u16, ep: u16) {
+	pub fn page_address_set(&mut self, sp: u16, ep: u16) -> Result<(), Error<T::Error, RST::Error>> {
 		self.write_parameters(0x2b, &[
 			(sp >> 8) as u8, (sp & 0xff) as u8,
 			(ep >> 8) as u8, (ep & 0xff) as u8,
-		]);
+		])
 	}
 
-	pub fn memory_write_start(&self) {
-		self.write_command(0x2c);
+	pub fn memory_write_start(&mut self) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_command(0x2c)
 	}
 
-	pub fn color_set(&self, data: &[u8; 128]) {
-		self.write_parameters(0x2d, data);
+	pub fn color_set(&mut self, data: &[u8; 128]) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_parameters(0x2d, data)
 	}
 
-	pub fn memory_read_start(&self) {
-		self.write_command(0x2e);
+	pub fn memory_read_start(&mut self) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_command(0x2e)
 	}
 
-	pub fn partial_area(&self, sr: u16, er: u16) {
+	pub fn partial_area(&mut self, sr: u16, er: u16) -> Result<(), Error<T::Error, RST::Error>> {
 		self.write_parameters(0x30, &[
 			(sr >> 8) as u8, (sr & 0xff) as u8,
 			(er >> 8) as u8, (er & 0xff) as u8,
-		]);
+		])
 	}
 
-	pub fn vertical_scrolling_definition(&self, tfa: u16, vsa: u16, bfa: u16) {
+	pub fn vertical_scrolling_definition(&mut self, tfa: u16, vsa: u16, bfa: u16) -> Result<(), Error<T::Error, RST::Error>> {
 		self.write_parameters(0x33, &[
 			(tfa >> 8) as u8, (tfa & 0xff) as u8,
 			(vsa >> 8) as u8, (vsa & 0xff) as u8,
 			(bfa >> 8) as u8, (bfa & 0xff) as u8,
-		]);
+		])
 	}
 
-	pub fn tearing_effect(&self, mode: TearingEffect) {
+	pub fn tearing_effect(&mut self, mode: TearingEffect) -> Result<(), Error<T::Error, RST::Error>> {
 		match mode {
 			TearingEffect::VBlankOnly => self.write_parameters(0x35, &[0u8]),
 			TearingEffect::HAndVBlank => self.write_parameters(0x35, &[1u8]),
 			_                         => self.write_command(0x34),
-		};
+		}
 	}
 
-	pub fn memory_access_control(&self, value: MemoryAccessControl) {
-		self.write_parameters(0x36, &value.raw);
+	pub fn memory_access_control(&mut self, value: MemoryAccessControl) -> Result<(), Error<T::Error, RST::Error>> {
+		self.madctl = value;
+		self.write_parameters(0x36, &value.raw)
 	}
 
-	pub fn vertical_scrolling_start_address(&self, vsp: u16) {
+	pub fn vertical_scrolling_start_address(&mut self, vsp: u16) -> Result<(), Error<T::Error, RST::Error>> {
 		self.write_parameters(0x37, &[
 			(vsp >> 8) as u8, (vsp & 0xff) as u8,
-		]);
+		])
 	}
 
-	pub fn idle_mode(&self, on: bool) {
+	pub fn idle_mode(&mut self, on: bool) -> Result<(), Error<T::Error, RST::Error>> {
 		let command = match on {
 			false => 0x38,
 			true  => 0x39,
 		};
-		self.write_command(command);
+		self.write_command(command)
 	}
 
-	pub fn pixel_format_set(&self, value: PixelFormat) {
-		self.write_parameters(0x3a, &value.raw);
+	pub fn pixel_format_set(&mut self, value: PixelFormat) -> Result<(), Error<T::Error, RST::Error>> {
+		if let Some(dbi) = value.dbi() {
+			self.format = dbi;
+		}
+		self.write_parameters(0x3a, &value.raw)
 	}
 
-	pub fn write_memory_continue(&self) {
-		self.write_command(0x3c);
+	pub fn write_memory_continue(&mut self) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_command(0x3c)
 	}
 
-	pub fn write_memory<I>(&self, iterable: I)
-		where I: IntoIterator<Item=u32>
+	/// Stream pixels following `memory_write_start` or
+	/// `write_memory_continue`, encoded for the current pixel format.
+	pub fn write_memory<C, I>(&mut self, iterable: I) -> Result<(), Error<T::Error, RST::Error>>
+		where C: Color,
+		      I: IntoIterator<Item=C>
 	{
-		self.iface.write_memory(iterable);
+		let format = self.format;
+		self.iface.write_memory(iterable.into_iter().map(move |c| c.to_raw(format)))?;
+		Ok(())
 	}
 
-	pub fn read_memory_continue(&self) {
-		self.write_command(0x3e);
+	pub fn read_memory_continue(&mut self) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_command(0x3e)
 	}
 
-	pub fn read_memory(&self, data: &mut [u32]) {
-		self.iface.read_memory(data);
+	/// Read pixels following `memory_read_start` or
+	/// `read_memory_continue`.
+	pub fn read_memory<C: Color>(&mut self, data: &mut [C]) -> Result<(), Error<T::Error, RST::Error>> {
+		let mut buffer = [0u32; READ_CHUNK_PIXELS];
+		for chunk in data.chunks_mut(READ_CHUNK_PIXELS) {
+			let raw = &mut buffer[..chunk.len()];
+			self.iface.read_memory(raw)?;
+			for (pixel, &value) in chunk.iter_mut().zip(raw.iter()) {
+				*pixel = C::from_raw_read(value);
+			}
+		}
+		Ok(())
 	}
 	
-	pub fn set_tear_scanline(&self, sts: u16) {
+	pub fn set_tear_scanline(&mut self, sts: u16) -> Result<(), Error<T::Error, RST::Error>> {
 		self.write_parameters(0x44, &[
 			(sts >> 8) as u8, (sts & 0xff) as u8,
-		]);
+		])
 	}
 
-	pub fn get_scanline(&self) -> u16 {
+	pub fn get_scanline(&mut self) -> Result<u16, Error<T::Error, RST::Error>> {
 		let mut result = [0u8; 2];
-		self.read_parameters(0x45, &mut result);
-		((result[0] as u16) << 8) | result[1] as u16
+		self.read_parameters(0x45, &mut result)?;
+		Ok(((result[0] as u16) << 8) | result[1] as u16)
 	}
 
-	pub fn write_display_brightness(&self, dbv: u8) {
-		self.write_parameters(0x51, &[dbv]);
+	pub fn write_display_brightness(&mut self, dbv: u8) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_parameters(0x51, &[dbv])
 	}
 
-	pub fn read_display_brightness(&self) -> u8 {
+	pub fn read_display_brightness(&mut self) -> Result<u8, Error<T::Error, RST::Error>> {
 		let mut result = [0u8; 1];
-		self.read_parameters(0x52, &mut result);
-		result[0]
+		self.read_parameters(0x52, &mut result)?;
+		Ok(result[0])
 	}
 
-	pub fn write_ctrl_display(&self, value: CtrlDisplay) {
-		self.write_parameters(0x53, &value.raw);
+	pub fn write_ctrl_display(&mut self, value: CtrlDisplay) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_parameters(0x53, &value.raw)
 	}
 
-	pub fn read_ctrl_display(&self) -> CtrlDisplay {
+	pub fn read_ctrl_display(&mut self) -> Result<CtrlDisplay, Error<T::Error, RST::Error>> {
 		let mut result = CtrlDisplay::default();
-		self.read_parameters(0x54, &mut result.raw);
-		result
+		self.read_parameters(0x54, &mut result.raw)?;
+		Ok(result)
 	}
 
-	pub fn write_cabc(&self, c: u8) {
-		self.write_parameters(0x55, &[c]);
+	pub fn write_cabc(&mut self, c: u8) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_parameters(0x55, &[c])
 	}
 
-	pub fn read_cabc(&self) -> u8 {
+	pub fn read_cabc(&mut self) -> Result<u8, Error<T::Error, RST::Error>> {
 		let mut result = [0u8; 1];
-		self.read_parameters(0x56, &mut result);
-		result[0]
+		self.read_parameters(0x56, &mut result)?;
+		Ok(result[0])
 	}
 
-	pub fn write_cabc_minimum_brightness(&self, cmb: u8) {
-		self.write_parameters(0x5e, &[cmb]);
+	pub fn write_cabc_minimum_brightness(&mut self, cmb: u8) -> Result<(), Error<T::Error, RST::Error>> {
+		self.write_parameters(0x5e, &[cmb])
 	}
 
-	pub fn read_cabc_minimum_brightness(&self) -> u8 {
+	pub fn read_cabc_minimum_brightness(&mut self) -> Result<u8, Error<T::Error, RST::Error>> {
 		let mut result = [0u8; 1];
-		self.read_parameters(0x5f, &mut result);
-		result[0]
+		self.read_parameters(0x5f, &mut result)?;
+		Ok(result[0])
 	}
 
-	pub fn read_id1(&self) -> u8 {
+	pub fn read_id1(&mut self) -> Result<u8, Error<T::Error, RST::Error>> {
 		let mut result = [0u8; 1];
-		self.read_parameters(0xda, &mut result);
-		result[0]
+		self.read_parameters(0xda, &mut result)?;
+		Ok(result[0])
 	}
 
-	pub fn read_id2(&self) -> u8 {
+	pub fn read_id2(&mut self) -> Result<u8, Error<T::Error, RST::Error>> {
 		let mut result = [0u8; 1];
-		self.read_parameters(0xdb, &mut result);
-		result[0]
+		self.read_parameters(0xdb, &mut result)?;
+		Ok(result[0])
 	}
 
-	pub fn read_id3(&self) -> u8 {
+	pub fn read_id3(&mut self) -> Result<u8, Error<T::Error, RST::Error>> {
 		let mut result = [0u8; 1];
-		self.read_parameters(0xdc, &mut result);
-		result[0]
+		self.read_parameters(0xdc, &mut result)?;
+		Ok(result[0])
 	}
-
-	// TODO: Implement extended command set
 }
@@ -0,0 +1,162 @@
+//! Interface implementation for the 8080-series MCU parallel bus ("8080-I"
+//! system), in 8-bit (DB[7:0]) and 16-bit (DB[15:0]) widths.
+
+use crate::{observe_bits_per_pixel, pixel_bytes, Interface};
+
+/// Width of the data port used for pixel transfers. Commands and
+/// parameters always travel on DB[7:0].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BusWidth {
+	Eight,
+	Sixteen,
+}
+
+/// Low-level access to an 8080-series bus: the D/CX line, the data port,
+/// and the WRX/RDX strobes. CSX is expected to be asserted by the
+/// implementation (or tied low) for the lifetime of the bus.
+pub trait ParallelBus {
+	type Error;
+
+	const WIDTH: BusWidth;
+
+	/// Drive D/CX: low for a command, high for parameters and pixel data.
+	fn set_data_command(&mut self, data: bool) -> Result<(), Self::Error>;
+	/// Drive the data port with `value` and pulse WRX.
+	fn write_strobe(&mut self, value: u16) -> Result<(), Self::Error>;
+	/// Pulse RDX and return the value sampled from the data port.
+	fn read_strobe(&mut self) -> Result<u16, Self::Error>;
+}
+
+/// Interface over an 8080-series parallel bus.
+///
+/// The pixel byte ordering depends on the COLMOD DBI format, which the
+/// interface tracks by observing Pixel Format Set (3Ah) and Software Reset
+/// (01h) as they pass through, and restores on `panel_reset`. Pixels
+/// passed to `write_memory` are RGB565 in the low 16 bits for 16 bits per
+/// pixel, or RGB666 in the low 18 bits for 18 bits per pixel.
+pub struct ParallelInterface<B> {
+	bus: B,
+	bits_per_pixel: u8,
+	dummy_pending: bool,
+	read_carry: Option<u8>,
+}
+
+impl<B> ParallelInterface<B>
+	where B: ParallelBus
+{
+	pub fn new(bus: B) -> ParallelInterface<B> {
+		ParallelInterface {
+			bus,
+			bits_per_pixel: 18,
+			dummy_pending: false,
+			read_carry: None,
+		}
+	}
+
+	/// Consume the interface, returning the underlying bus.
+	pub fn release(self) -> B {
+		self.bus
+	}
+
+	fn observe_command(&mut self, command: u8, data: &[u8]) {
+		observe_bits_per_pixel(&mut self.bits_per_pixel, command, data);
+		self.dummy_pending = command == 0x2e || command == 0x3e;
+		self.read_carry = None;
+	}
+
+	fn read_byte(&mut self) -> Result<u8, B::Error> {
+		if let Some(byte) = self.read_carry.take() {
+			return Ok(byte);
+		}
+		if self.dummy_pending {
+			self.bus.read_strobe()?;
+			self.dummy_pending = false;
+		}
+		let word = self.bus.read_strobe()?;
+		match B::WIDTH {
+			BusWidth::Eight => Ok(word as u8),
+			BusWidth::Sixteen => {
+				self.read_carry = Some(word as u8);
+				Ok((word >> 8) as u8)
+			},
+		}
+	}
+}
+
+impl<B> Interface for ParallelInterface<B>
+	where B: ParallelBus
+{
+	type Error = B::Error;
+
+	fn write_parameters(&mut self, command: u8, data: &[u8]) -> Result<(), Self::Error> {
+		self.observe_command(command, data);
+		self.bus.set_data_command(false)?;
+		self.bus.write_strobe(command as u16)?;
+		if !data.is_empty() {
+			self.bus.set_data_command(true)?;
+			for &value in data {
+				self.bus.write_strobe(value as u16)?;
+			}
+		}
+		Ok(())
+	}
+
+	fn write_memory<I>(&mut self, iterable: I) -> Result<(), Self::Error>
+		where I: IntoIterator<Item=u32>
+	{
+		self.bus.set_data_command(true)?;
+
+		// Pixels are flattened into a byte stream, which is then sent one
+		// byte per strobe on an 8-bit bus, or two (high first) on a 16-bit
+		// bus. An odd byte left at the end is padded with zero.
+		let mut pending: Option<u8> = None;
+		for pixel in iterable {
+			let (bytes, count) = pixel_bytes(pixel, self.bits_per_pixel);
+			for &byte in &bytes[..count] {
+				match B::WIDTH {
+					BusWidth::Eight => self.bus.write_strobe(byte as u16)?,
+					BusWidth::Sixteen => match pending.take() {
+						Some(high) => self.bus.write_strobe(((high as u16) << 8) | byte as u16)?,
+						None => pending = Some(byte),
+					},
+				}
+			}
+		}
+		if let Some(high) = pending {
+			self.bus.write_strobe((high as u16) << 8)?;
+		}
+		Ok(())
+	}
+
+	fn read_parameters(&mut self, command: u8, data: &mut [u8]) -> Result<(), Self::Error> {
+		self.observe_command(command, &[]);
+		self.bus.set_data_command(false)?;
+		self.bus.write_strobe(command as u16)?;
+		self.bus.set_data_command(true)?;
+
+		// Every parameter read starts with a dummy read cycle.
+		self.bus.read_strobe()?;
+		for value in data.iter_mut() {
+			*value = self.bus.read_strobe()? as u8;
+		}
+		Ok(())
+	}
+
+	fn read_memory(&mut self, data: &mut [u32]) -> Result<(), Self::Error> {
+		self.bus.set_data_command(true)?;
+		for pixel in data.iter_mut() {
+			let r = self.read_byte()?;
+			let g = self.read_byte()?;
+			let b = self.read_byte()?;
+			*pixel = ((r as u32) << 16) | ((g as u32) << 8) | b as u32;
+		}
+		Ok(())
+	}
+
+	fn panel_reset(&mut self) -> Result<(), Self::Error> {
+		self.bits_per_pixel = 18;
+		self.dummy_pending = false;
+		self.read_carry = None;
+		Ok(())
+	}
+}
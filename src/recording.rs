@@ -0,0 +1,233 @@
+//! Mock Interface that records the command stream, optionally checking it
+//! against a script of expected transactions.
+
+use core::convert::Infallible;
+use core::fmt;
+use std::collections::VecDeque;
+use std::vec::Vec;
+
+use crate::{decode_stream, Interface};
+
+/// One call made on an Interface.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Transaction {
+	/// `write_parameters`: a command byte and its parameters.
+	Command {
+		command: u8,
+		parameters: Vec<u8>,
+	},
+	/// `write_memory`: the pixels, right-aligned as passed in.
+	WriteMemory(Vec<u32>),
+	/// `read_parameters`: the command byte and the bytes returned.
+	ReadParameters {
+		command: u8,
+		data: Vec<u8>,
+	},
+	/// `read_memory`: the pixels returned. Consecutive calls, as made by a
+	/// Controller reading in chunks, form a single transaction.
+	ReadMemory(Vec<u32>),
+}
+
+impl Transaction {
+	pub fn command(command: u8, parameters: &[u8]) -> Transaction {
+		Transaction::Command {
+			command,
+			parameters: parameters.to_vec(),
+		}
+	}
+
+	pub fn write_memory(pixels: &[u32]) -> Transaction {
+		Transaction::WriteMemory(pixels.to_vec())
+	}
+
+	/// A register read of `command` that returns `data`.
+	pub fn read_parameters(command: u8, data: &[u8]) -> Transaction {
+		Transaction::ReadParameters {
+			command,
+			data: data.to_vec(),
+		}
+	}
+
+	/// A memory read that returns `pixels`, in the 0x00RRGGBB read format.
+	pub fn read_memory(pixels: &[u32]) -> Transaction {
+		Transaction::ReadMemory(pixels.to_vec())
+	}
+}
+
+/// Most pixels shown when formatting a memory transaction.
+const SHOWN_PIXELS: usize = 8;
+
+/// Write `command` and `parameters` as decoded ILI9341 commands.
+fn write_command(f: &mut fmt::Formatter, command: u8, parameters: &[u8]) -> fmt::Result {
+	let bytes = core::iter::once((true, command)).chain(parameters.iter().map(|&byte| (false, byte)));
+	for (index, decoded) in decode_stream(bytes).enumerate() {
+		match index {
+			0 => write!(f, "{}", decoded)?,
+			_ => write!(f, ", {}", decoded)?,
+		}
+	}
+	Ok(())
+}
+
+fn write_pixels(f: &mut fmt::Formatter, pixels: &[u32]) -> fmt::Result {
+	write!(f, "{} pixels {:06x?}", pixels.len(), &pixels[..pixels.len().min(SHOWN_PIXELS)])?;
+	match pixels.len() > SHOWN_PIXELS {
+		false => Ok(()),
+		true  => write!(f, "..."),
+	}
+}
+
+impl fmt::Display for Transaction {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			Transaction::Command { command, ref parameters } => write_command(f, command, parameters),
+			Transaction::WriteMemory(ref pixels) => {
+				write!(f, "memory write of ")?;
+				write_pixels(f, pixels)
+			},
+			Transaction::ReadParameters { command, ref data } => {
+				write!(f, "read of ")?;
+				write_command(f, command, &[])?;
+				write!(f, " returning {:02x?}", data)
+			},
+			Transaction::ReadMemory(ref pixels) => {
+				write!(f, "memory read of ")?;
+				write_pixels(f, pixels)
+			},
+		}
+	}
+}
+
+/// Interface that logs every call as a Transaction.
+///
+/// Created with `new`, it accepts anything and reads return zeros. Created
+/// with `scripted`, each call must match the next expected transaction,
+/// reads return the scripted data, and anything else panics; call `done`
+/// at the end of the test to check the whole script was used. A scripted
+/// memory read is served across as many `read_memory` calls as it takes.
+#[derive(Clone, Debug, Default)]
+pub struct RecordingInterface {
+	log: Vec<Transaction>,
+	script: Option<VecDeque<Transaction>>,
+}
+
+impl RecordingInterface {
+	pub fn new() -> RecordingInterface {
+		RecordingInterface::default()
+	}
+
+	/// Expect exactly `expected`, in order.
+	pub fn scripted<I>(expected: I) -> RecordingInterface
+		where I: IntoIterator<Item=Transaction>
+	{
+		RecordingInterface {
+			log: Vec::new(),
+			script: Some(expected.into_iter().collect()),
+		}
+	}
+
+	/// Transactions so far, oldest first.
+	pub fn transactions(&self) -> &[Transaction] {
+		&self.log
+	}
+
+	/// Remove and return the transactions so far.
+	pub fn take_transactions(&mut self) -> Vec<Transaction> {
+		core::mem::take(&mut self.log)
+	}
+
+	/// Panic if scripted transactions remain unused.
+	pub fn done(&self) {
+		if let Some(script) = &self.script {
+			if let Some(next) = script.front() {
+				panic!("{} scripted transactions not performed, next {}", script.len(), next);
+			}
+		}
+	}
+
+	/// Next scripted transaction, or None when not scripted.
+	fn expect(&mut self, actual: &Transaction) -> Option<Transaction> {
+		let script = self.script.as_mut()?;
+		match script.pop_front() {
+			Some(expected) => Some(expected),
+			None => panic!("unexpected {} after the end of the script", actual),
+		}
+	}
+
+	/// Check a write against the script, then log it.
+	fn write(&mut self, actual: Transaction) {
+		if let Some(expected) = self.expect(&actual) {
+			if let (Transaction::WriteMemory(a), Transaction::WriteMemory(e)) = (&actual, &expected) {
+				if let Some(index) = a.iter().zip(e.iter()).position(|(a, e)| a != e) {
+					panic!("transaction {}: pixel {} of the memory write is {:06x}, expected {:06x}",
+						self.log.len(), index, a[index], e[index]);
+				}
+			}
+			if actual != expected {
+				panic!("transaction {}: expected {}, got {}", self.log.len(), expected, actual);
+			}
+		}
+		self.log.push(actual);
+	}
+
+	/// Take the next `len` pixels of the scripted memory read, removing it
+	/// from the script once used up. None when not scripted.
+	fn scripted_read(&mut self, len: usize) -> Option<Vec<u32>> {
+		let index = self.log.len();
+		let script = self.script.as_mut()?;
+		let canned = match script.front_mut() {
+			Some(Transaction::ReadMemory(canned)) if canned.len() >= len => canned.drain(..len).collect(),
+			Some(expected) => panic!("transaction {}: expected {}, got a {}-pixel memory read", index, expected, len),
+			None => panic!("unexpected {}-pixel memory read after the end of the script", len),
+		};
+		if let Some(Transaction::ReadMemory(rest)) = script.front() {
+			if rest.is_empty() {
+				script.pop_front();
+			}
+		}
+		Some(canned)
+	}
+}
+
+impl Interface for RecordingInterface {
+	type Error = Infallible;
+
+	fn write_parameters(&mut self, command: u8, data: &[u8]) -> Result<(), Self::Error> {
+		self.write(Transaction::command(command, data));
+		Ok(())
+	}
+
+	fn write_memory<I>(&mut self, iterable: I) -> Result<(), Self::Error>
+		where I: IntoIterator<Item=u32>
+	{
+		self.write(Transaction::WriteMemory(iterable.into_iter().collect()));
+		Ok(())
+	}
+
+	fn read_parameters(&mut self, command: u8, data: &mut [u8]) -> Result<(), Self::Error> {
+		data.fill(0);
+		let actual = Transaction::ReadParameters { command, data: data.to_vec() };
+		match self.expect(&actual) {
+			Some(Transaction::ReadParameters { command: c, data: ref canned }) if c == command && canned.len() == data.len() =>
+				data.copy_from_slice(canned),
+			Some(expected) =>
+				panic!("transaction {}: expected {}, got a {}-byte {}", self.log.len(), expected, data.len(),
+					Transaction::read_parameters(command, &[])),
+			None => (),
+		}
+		self.log.push(Transaction::read_parameters(command, data));
+		Ok(())
+	}
+
+	fn read_memory(&mut self, data: &mut [u32]) -> Result<(), Self::Error> {
+		match self.scripted_read(data.len()) {
+			Some(canned) => data.copy_from_slice(&canned),
+			None => data.fill(0),
+		}
+		match self.log.last_mut() {
+			Some(Transaction::ReadMemory(pixels)) => pixels.extend_from_slice(data),
+			_ => self.log.push(Transaction::read_memory(data)),
+		}
+		Ok(())
+	}
+}
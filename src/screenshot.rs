@@ -0,0 +1,146 @@
+//! Screenshots: GRAM read back and serialized as a BMP or PPM image.
+
+use core::convert::Infallible;
+use core::fmt;
+use std::io::{self, Write};
+
+#[cfg(feature = "async")]
+use crate::asynch::{AsyncController, AsyncInterface};
+use crate::{Controller, Error, Interface, Rect, ResetPin, Rgb666, HEIGHT};
+
+/// Image file format written by `Controller::screenshot`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ScreenshotFormat {
+	/// Windows bitmap, 24 bits per pixel, stored top-down.
+	Bmp,
+	/// Binary Netpbm pixmap (P6), 8 bits per channel.
+	Ppm,
+}
+
+/// Errors produced while taking a screenshot.
+#[derive(Debug)]
+pub enum ScreenshotError<E, P = Infallible> {
+	/// Reading the panel failed.
+	Controller(Error<E, P>),
+	/// Writing to the sink failed.
+	Io(io::Error),
+}
+
+impl<E, P> From<Error<E, P>> for ScreenshotError<E, P> {
+	fn from(e: Error<E, P>) -> ScreenshotError<E, P> {
+		ScreenshotError::Controller(e)
+	}
+}
+
+impl<E, P> From<io::Error> for ScreenshotError<E, P> {
+	fn from(e: io::Error) -> ScreenshotError<E, P> {
+		ScreenshotError::Io(e)
+	}
+}
+
+impl<E: fmt::Debug, P: fmt::Debug> fmt::Display for ScreenshotError<E, P> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			ScreenshotError::Controller(ref e) => write!(f, "{}", e),
+			ScreenshotError::Io(ref e) => write!(f, "write error: {}", e),
+		}
+	}
+}
+
+impl<E: fmt::Debug, P: fmt::Debug> std::error::Error for ScreenshotError<E, P> {}
+
+/// Size of the BMP file and info headers.
+const BMP_HEADER_LEN: u32 = 14 + 40;
+
+/// Bytes in one BMP row of `width` pixels, padded to a multiple of four.
+fn bmp_stride(width: u16) -> u32 {
+	(width as u32 * 3 + 3) & !3
+}
+
+fn write_header<W: Write>(format: ScreenshotFormat, width: u16, height: u16, sink: &mut W) -> io::Result<()> {
+	match format {
+		ScreenshotFormat::Bmp => {
+			let image_len = bmp_stride(width) * height as u32;
+			sink.write_all(b"BM")?;
+			sink.write_all(&(BMP_HEADER_LEN + image_len).to_le_bytes())?;
+			sink.write_all(&0u32.to_le_bytes())?;
+			sink.write_all(&BMP_HEADER_LEN.to_le_bytes())?;
+
+			sink.write_all(&40u32.to_le_bytes())?;
+			sink.write_all(&(width as i32).to_le_bytes())?;
+			// Negative height: rows are stored top-down, in read order.
+			sink.write_all(&(-(height as i32)).to_le_bytes())?;
+			sink.write_all(&1u16.to_le_bytes())?;
+			sink.write_all(&24u16.to_le_bytes())?;
+			sink.write_all(&0u32.to_le_bytes())?;
+			sink.write_all(&image_len.to_le_bytes())?;
+			// 72 DPI.
+			sink.write_all(&2835u32.to_le_bytes())?;
+			sink.write_all(&2835u32.to_le_bytes())?;
+			sink.write_all(&0u32.to_le_bytes())?;
+			sink.write_all(&0u32.to_le_bytes())
+		},
+		ScreenshotFormat::Ppm => write!(sink, "P6\n{} {}\n255\n", width, height),
+	}
+}
+
+fn write_row<W: Write>(format: ScreenshotFormat, row: &[Rgb666], sink: &mut W) -> io::Result<()> {
+	let mut bytes = [0u8; HEIGHT as usize * 3 + 3];
+	for (pixel, color) in bytes.chunks_exact_mut(3).zip(row.iter()) {
+		let [r, g, b] = color.to_rgb888();
+		pixel.copy_from_slice(&match format {
+			ScreenshotFormat::Bmp => [b, g, r],
+			ScreenshotFormat::Ppm => [r, g, b],
+		});
+	}
+	let len = match format {
+		ScreenshotFormat::Bmp => bmp_stride(row.len() as u16) as usize,
+		ScreenshotFormat::Ppm => row.len() * 3,
+	};
+	sink.write_all(&bytes[..len])
+}
+
+impl<T: Interface, RST: ResetPin> Controller<T, RST> {
+	/// Read the whole panel, in the current orientation, and write it to
+	/// `sink` as an image.
+	pub fn screenshot<W: Write>(&mut self, format: ScreenshotFormat, sink: &mut W) -> Result<(), ScreenshotError<T::Error, RST::Error>> {
+		let (width, height) = self.dimensions();
+		self.screenshot_rect(Rect::new(0, 0, width, height), format, sink)
+	}
+
+	/// Read `rect` and write it to `sink` as an image, one row at a time.
+	/// Fails with `Error::OutOfBounds`, before writing anything, if `rect`
+	/// does not fit the panel.
+	pub fn screenshot_rect<W: Write>(&mut self, rect: Rect, format: ScreenshotFormat, sink: &mut W) -> Result<(), ScreenshotError<T::Error, RST::Error>> {
+		let mut row = [Rgb666::BLACK; HEIGHT as usize];
+		// Check the bounds before anything reaches the sink.
+		self.set_window(rect)?;
+		write_header(format, rect.width, rect.height, sink)?;
+		for y in rect.y..rect.y + rect.height {
+			let row = &mut row[..rect.width as usize];
+			self.read_rect(Rect::new(rect.x, y, rect.width, 1), row)?;
+			write_row(format, row, sink)?;
+		}
+		Ok(())
+	}
+}
+
+#[cfg(feature = "async")]
+impl<T: AsyncInterface, RST: ResetPin> AsyncController<T, RST> {
+	pub async fn screenshot<W: Write>(&mut self, format: ScreenshotFormat, sink: &mut W) -> Result<(), ScreenshotError<T::Error, RST::Error>> {
+		let (width, height) = self.dimensions();
+		self.screenshot_rect(Rect::new(0, 0, width, height), format, sink).await
+	}
+
+	pub async fn screenshot_rect<W: Write>(&mut self, rect: Rect, format: ScreenshotFormat, sink: &mut W) -> Result<(), ScreenshotError<T::Error, RST::Error>> {
+		let mut row = [Rgb666::BLACK; HEIGHT as usize];
+		self.set_window(rect).await?;
+		write_header(format, rect.width, rect.height, sink)?;
+		for y in rect.y..rect.y + rect.height {
+			let row = &mut row[..rect.width as usize];
+			self.read_rect(Rect::new(rect.x, y, rect.width, 1), row).await?;
+			write_row(format, row, sink)?;
+		}
+		Ok(())
+	}
+}
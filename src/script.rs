@@ -0,0 +1,209 @@
+//! Command scripts: init sequences and splash images stored as compact
+//! byte strings and replayed through a Controller.
+//!
+//! A script is a sequence of entries:
+//!
+//! - `command, count, parameters[count]`: send a command. Known commands
+//!   must carry exactly the parameters they take, except that Display
+//!   Function Control (B6h) may leave out its fourth. Commands outside the
+//!   standard and extended sets, such as the undocumented EFh found in
+//!   vendor init sequences, are sent as given. Read commands are rejected.
+//! - `0xff, ms_high, ms_low`: wait for a number of milliseconds. 0xFF is
+//!   not a panel command.
+//!
+//! Memory Write (2Ch) and Write Memory Continue (3Ch) carry pixels as
+//! big-endian RGB565, two bytes each, and are sent in the pixel format the
+//! Controller has set.
+
+#[cfg(feature = "async")]
+use crate::asynch::{AsyncController, AsyncInterface};
+use crate::decode::{length, Length};
+#[cfg(feature = "embedded-hal")]
+use crate::{Controller, Error, Interface, MemoryAccessControl, PixelFormat, ResetPin, Rgb565};
+
+/// Opcode introducing a delay entry.
+const DELAY: u8 = 0xff;
+
+/// One decoded script entry.
+#[cfg(feature = "embedded-hal")]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Entry<'a> {
+	Command(u8, &'a [u8]),
+	DelayMs(u16),
+}
+
+/// Whether `command` may carry `count` parameter bytes in a script.
+fn valid_count(command: u8, count: usize) -> bool {
+	match (command, length(command)) {
+		(0x2c | 0x3c, _) => count % 2 == 0,
+		(_, Length::Fixed(n)) => count == n,
+		(_, Length::Range(min, max)) => (min..=max).contains(&count),
+		(_, Length::Stream) => false,
+		(_, Length::Unknown) => true,
+	}
+}
+
+/// Decode the entry at the start of `script`, returning it and its
+/// length, or None if it is malformed.
+#[cfg(feature = "embedded-hal")]
+fn entry(script: &[u8]) -> Option<(Entry<'_>, usize)> {
+	match *script {
+		[DELAY, high, low, ..] => Some((Entry::DelayMs(u16::from_be_bytes([high, low])), 3)),
+		[DELAY, ..] => None,
+		[command, count, ref rest @ ..] => {
+			let parameters = rest.get(..count as usize)?;
+			match valid_count(command, parameters.len()) {
+				true  => Some((Entry::Command(command, parameters), 2 + parameters.len())),
+				false => None,
+			}
+		},
+		_ => None,
+	}
+}
+
+/// Iterate over the entries of a script already checked by `validate`.
+#[cfg(feature = "embedded-hal")]
+fn entries(script: &[u8]) -> impl Iterator<Item=Entry<'_>> {
+	let mut rest = script;
+	core::iter::from_fn(move || {
+		let (entry, len) = entry(rest)?;
+		rest = &rest[len..];
+		Some(entry)
+	})
+}
+
+/// Check every entry of `script` before anything is sent.
+#[cfg(feature = "embedded-hal")]
+fn validate<E, P>(script: &[u8]) -> Result<(), Error<E, P>> {
+	let mut offset = 0;
+	while offset < script.len() {
+		let (_, len) = entry(&script[offset..]).ok_or(Error::InvalidScript { offset })?;
+		offset += len;
+	}
+	Ok(())
+}
+
+#[cfg(feature = "embedded-hal")]
+fn pixels(data: &[u8]) -> impl Iterator<Item=Rgb565> + '_ {
+	data.chunks_exact(2).map(|pair| Rgb565::from_bits(u16::from_be_bytes([pair[0], pair[1]])))
+}
+
+#[cfg(feature = "embedded-hal")]
+impl<T: Interface, RST: ResetPin> Controller<T, RST> {
+	/// Play back `script`, waiting on `delay` for its delay entries. The
+	/// whole script is checked first, so a malformed one fails with
+	/// `Error::InvalidScript` without sending anything.
+	pub fn run_script<D>(&mut self, script: &[u8], delay: &mut D) -> Result<(), Error<T::Error, RST::Error>>
+		where D: embedded_hal::delay::DelayNs
+	{
+		validate(script)?;
+		for entry in entries(script) {
+			match entry {
+				Entry::DelayMs(ms) => delay.delay_ms(ms as u32),
+				Entry::Command(0x01, _) => self.software_reset()?,
+				Entry::Command(0x36, p) => self.memory_access_control(MemoryAccessControl { raw: [p[0]] })?,
+				Entry::Command(0x3a, p) => self.pixel_format_set(PixelFormat { raw: [p[0]] })?,
+				Entry::Command(command @ (0x2c | 0x3c), data) => {
+					self.write_command(command)?;
+					if !data.is_empty() {
+						self.write_memory(pixels(data))?;
+					}
+				},
+				Entry::Command(command, p) => self.write_parameters(command, p)?,
+			}
+		}
+		Ok(())
+	}
+}
+
+#[cfg(feature = "async")]
+impl<T: AsyncInterface, RST: ResetPin> AsyncController<T, RST> {
+	pub async fn run_script<D>(&mut self, script: &[u8], delay: &mut D) -> Result<(), Error<T::Error, RST::Error>>
+		where D: embedded_hal_async::delay::DelayNs
+	{
+		validate(script)?;
+		for entry in entries(script) {
+			match entry {
+				Entry::DelayMs(ms) => delay.delay_ms(ms as u32).await,
+				Entry::Command(0x01, _) => self.software_reset().await?,
+				Entry::Command(0x36, p) => self.memory_access_control(MemoryAccessControl { raw: [p[0]] }).await?,
+				Entry::Command(0x3a, p) => self.pixel_format_set(PixelFormat { raw: [p[0]] }).await?,
+				Entry::Command(command @ (0x2c | 0x3c), data) => {
+					self.write_command(command).await?;
+					if !data.is_empty() {
+						self.write_memory(pixels(data)).await?;
+					}
+				},
+				Entry::Command(command, p) => self.write_parameters(command, p).await?,
+			}
+		}
+		Ok(())
+	}
+}
+
+#[cfg(feature = "std")]
+pub use self::encoder::ScriptEncoder;
+
+#[cfg(feature = "std")]
+mod encoder {
+	use std::vec::Vec;
+
+	use super::{valid_count, DELAY};
+	use crate::Rgb565;
+
+	/// Most pixels carried by one memory write entry.
+	const ENTRY_PIXELS: usize = 127;
+
+	/// Builds a script, for example from a build script writing it to
+	/// `OUT_DIR` to be embedded with `include_bytes!`.
+	#[derive(Clone, Debug, Default)]
+	pub struct ScriptEncoder {
+		bytes: Vec<u8>,
+	}
+
+	impl ScriptEncoder {
+		pub fn new() -> ScriptEncoder {
+			ScriptEncoder::default()
+		}
+
+		/// Append a command with its parameters.
+		///
+		/// Panics if a script cannot carry them: a read command, a known
+		/// command with the wrong number of parameters, more than 255
+		/// parameter bytes, or the delay opcode 0xFF.
+		pub fn command(mut self, command: u8, parameters: &[u8]) -> ScriptEncoder {
+			assert!(command != DELAY, "0xff is the script delay opcode");
+			assert!(parameters.len() <= u8::MAX as usize, "too many parameters for {:02X}h", command);
+			assert!(valid_count(command, parameters.len()), "invalid parameters for {:02X}h: {:02x?}", command, parameters);
+			self.bytes.push(command);
+			self.bytes.push(parameters.len() as u8);
+			self.bytes.extend_from_slice(parameters);
+			self
+		}
+
+		pub fn delay_ms(mut self, ms: u16) -> ScriptEncoder {
+			self.bytes.push(DELAY);
+			self.bytes.extend_from_slice(&ms.to_be_bytes());
+			self
+		}
+
+		/// Append a Memory Write of `pixels`, continued with Write Memory
+		/// Continue entries as needed.
+		pub fn memory_write(mut self, pixels: &[Rgb565]) -> ScriptEncoder {
+			let mut command = 0x2c;
+			let mut chunks = pixels.chunks(ENTRY_PIXELS);
+			let first = chunks.next().unwrap_or(&[]);
+			for chunk in core::iter::once(first).chain(chunks) {
+				let data: Vec<u8> = chunk.iter().flat_map(|p| p.bits().to_be_bytes()).collect();
+				self = self.command(command, &data);
+				command = 0x3c;
+			}
+			self
+		}
+
+		/// The encoded script.
+		pub fn finish(self) -> Vec<u8> {
+			self.bytes
+		}
+	}
+}
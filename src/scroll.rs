@@ -0,0 +1,144 @@
+//! Hardware vertical scrolling: Vertical Scrolling Definition (33h) and
+//! Vertical Scrolling Start Address (37h) driven from a validated region.
+//!
+//! Rows here are GRAM lines along the 320-line side of the panel, the page
+//! addresses of the portrait orientation.
+
+#[cfg(feature = "async")]
+use crate::asynch::{AsyncController, AsyncInterface};
+use crate::{Controller, Error, Interface, ResetPin, HEIGHT};
+
+/// A split of the panel into a top fixed area, a scrolling area and a
+/// bottom fixed area, together with the current scroll offset.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ScrollRegion {
+	top_fixed: u16,
+	scroll_height: u16,
+	bottom_fixed: u16,
+	offset: u16,
+}
+
+impl ScrollRegion {
+	/// Areas of `top_fixed`, `scroll_height` and `bottom_fixed` rows, or
+	/// None unless they add up to the 320 rows of the panel with a
+	/// non-empty scrolling area.
+	pub fn new(top_fixed: u16, scroll_height: u16, bottom_fixed: u16) -> Option<ScrollRegion> {
+		let total = top_fixed as u32 + scroll_height as u32 + bottom_fixed as u32;
+		match scroll_height > 0 && total == HEIGHT as u32 {
+			true => Some(ScrollRegion {
+				top_fixed,
+				scroll_height,
+				bottom_fixed,
+				offset: 0,
+			}),
+			false => None,
+		}
+	}
+
+	/// The whole panel scrolling, with no fixed areas.
+	pub fn full_screen() -> ScrollRegion {
+		ScrollRegion {
+			top_fixed: 0,
+			scroll_height: HEIGHT,
+			bottom_fixed: 0,
+			offset: 0,
+		}
+	}
+
+	pub fn top_fixed(&self) -> u16 {
+		self.top_fixed
+	}
+
+	pub fn scroll_height(&self) -> u16 {
+		self.scroll_height
+	}
+
+	pub fn bottom_fixed(&self) -> u16 {
+		self.bottom_fixed
+	}
+
+	/// Rows the scrolling area has moved up, in 0..scroll_height.
+	pub fn offset(&self) -> u16 {
+		self.offset
+	}
+
+	/// Same areas, scrolled to `offset` (wrapped to the scrolling area).
+	pub fn with_offset(mut self, offset: u16) -> ScrollRegion {
+		self.offset = offset % self.scroll_height;
+		self
+	}
+
+	/// Same areas, scrolled by a further `rows`; negative values scroll
+	/// back down.
+	pub fn scrolled_by(self, rows: i32) -> ScrollRegion {
+		let offset = (self.offset as i32 + rows).rem_euclid(self.scroll_height as i32);
+		self.with_offset(offset as u16)
+	}
+
+	/// Vertical Scrolling Start Address: the GRAM row shown at the top of
+	/// the scrolling area.
+	pub fn start_address(&self) -> u16 {
+		self.top_fixed + self.offset
+	}
+
+	/// GRAM row shown on screen row `row`, or None past the panel.
+	pub fn physical_row(&self, row: u16) -> Option<u16> {
+		let bottom = self.top_fixed + self.scroll_height;
+		match row {
+			r if r < self.top_fixed => Some(r),
+			r if r < bottom => Some(self.top_fixed + (r - self.top_fixed + self.offset) % self.scroll_height),
+			r if r < HEIGHT => Some(r),
+			_ => None,
+		}
+	}
+
+	/// Screen row showing GRAM row `row`, or None past the panel; the
+	/// inverse of `physical_row`.
+	pub fn logical_row(&self, row: u16) -> Option<u16> {
+		let bottom = self.top_fixed + self.scroll_height;
+		match row {
+			r if r < self.top_fixed => Some(r),
+			r if r < bottom => Some(self.top_fixed + (r - self.top_fixed + self.scroll_height - self.offset) % self.scroll_height),
+			r if r < HEIGHT => Some(r),
+			_ => None,
+		}
+	}
+}
+
+impl Default for ScrollRegion {
+	fn default() -> ScrollRegion {
+		ScrollRegion::full_screen()
+	}
+}
+
+impl<T: Interface, RST: ResetPin> Controller<T, RST> {
+	/// Define the areas of `region` and scroll to its offset.
+	pub fn set_scroll_region(&mut self, region: &ScrollRegion) -> Result<(), Error<T::Error, RST::Error>> {
+		self.vertical_scrolling_definition(region.top_fixed, region.scroll_height, region.bottom_fixed)?;
+		self.vertical_scrolling_start_address(region.start_address())
+	}
+
+	/// Scroll the area defined by `region` up by `rows`, or down for
+	/// negative values, and record the new offset in `region`.
+	pub fn scroll_by(&mut self, region: &mut ScrollRegion, rows: i32) -> Result<(), Error<T::Error, RST::Error>> {
+		let scrolled = region.scrolled_by(rows);
+		self.vertical_scrolling_start_address(scrolled.start_address())?;
+		*region = scrolled;
+		Ok(())
+	}
+}
+
+#[cfg(feature = "async")]
+impl<T: AsyncInterface, RST: ResetPin> AsyncController<T, RST> {
+	pub async fn set_scroll_region(&mut self, region: &ScrollRegion) -> Result<(), Error<T::Error, RST::Error>> {
+		self.vertical_scrolling_definition(region.top_fixed, region.scroll_height, region.bottom_fixed).await?;
+		self.vertical_scrolling_start_address(region.start_address()).await
+	}
+
+	pub async fn scroll_by(&mut self, region: &mut ScrollRegion, rows: i32) -> Result<(), Error<T::Error, RST::Error>> {
+		let scrolled = region.scrolled_by(rows);
+		self.vertical_scrolling_start_address(scrolled.start_address()).await?;
+		*region = scrolled;
+		Ok(())
+	}
+}
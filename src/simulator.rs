@@ -0,0 +1,311 @@
+//! Host-side model of the panel, for testing code that drives a Controller
+//! without hardware.
+
+use core::convert::Infallible;
+use std::vec;
+use std::vec::Vec;
+
+use crate::{
+	observe_bits_per_pixel, Frame, Interface, MemoryAccessControl,
+	Rgb111, Rgb565, Rgb666, HEIGHT, WIDTH,
+};
+
+/// Interface that models the ILI9341 frame memory.
+///
+/// Decodes Software Reset, Column/Page Address Set, Memory Write and Write
+/// Memory Continue, Memory Read and Read Memory Continue, Memory Access
+/// Control, Pixel Format Set, Vertical Scrolling Definition and Start
+/// Address, Normal Display Mode On, Display Inversion, Idle Mode and
+/// Display On/Off.
+/// Other commands are accepted and ignored, and reads of other registers
+/// return zeros.
+///
+/// GRAM holds 240 columns by 320 rows of 18-bit pixels, addressed through
+/// the column/page window with the address counter wrapping as on the
+/// panel. The visible image is modelled on the common modules the
+/// Orientation presets target, which show GRAM columns right to left, so
+/// `Orientation::Portrait` appears upright.
+pub struct SimulatedPanel {
+	gram: Vec<Rgb666>,
+	madctl: MemoryAccessControl,
+	bits_per_pixel: u8,
+	columns: (u16, u16),
+	pages: (u16, u16),
+	counter: (u16, u16),
+	scroll_definition: (u16, u16, u16),
+	scroll_start: u16,
+	scrolling: bool,
+	inversion: bool,
+	idle: bool,
+	display_on: bool,
+	/// Memory read awaiting its first pixel, with the counter still to be
+	/// moved to the window start for Memory Read (2Eh).
+	read_start: bool,
+}
+
+impl SimulatedPanel {
+	/// A panel in its reset state, with GRAM cleared to black.
+	pub fn new() -> SimulatedPanel {
+		SimulatedPanel {
+			gram: vec![Rgb666::BLACK; WIDTH as usize * HEIGHT as usize],
+			madctl: MemoryAccessControl::default(),
+			bits_per_pixel: 18,
+			columns: (0, WIDTH - 1),
+			pages: (0, HEIGHT - 1),
+			counter: (0, 0),
+			scroll_definition: (0, HEIGHT, 0),
+			scroll_start: 0,
+			scrolling: false,
+			inversion: false,
+			idle: false,
+			display_on: false,
+			read_start: false,
+		}
+	}
+
+	/// Restore the register state of a Software Reset or RESX pulse; GRAM
+	/// is kept.
+	fn reset(&mut self) {
+		let gram = core::mem::take(&mut self.gram);
+		*self = SimulatedPanel {
+			gram,
+			..SimulatedPanel::new()
+		};
+	}
+
+	/// GRAM contents at memory `column` and `row`, independent of Memory
+	/// Access Control.
+	pub fn gram(&self, column: u16, row: u16) -> Rgb666 {
+		self.gram[row as usize * WIDTH as usize + column as usize]
+	}
+
+	/// GRAM contents at `x`, `y` in the coordinates of the current Memory
+	/// Access Control setting: the pixel a window write at that position
+	/// lands on.
+	pub fn pixel(&self, x: u16, y: u16) -> Rgb666 {
+		let (column, row) = self.memory_address(x, y).expect("pixel out of bounds");
+		self.gram(column, row)
+	}
+
+	/// Visible color at `x`, `y` in upright portrait coordinates, after
+	/// vertical scrolling, inversion and the 8-color reduction of idle
+	/// mode. Black while the display is off.
+	pub fn screen(&self, x: u16, y: u16) -> Rgb666 {
+		if !self.display_on {
+			return Rgb666::BLACK;
+		}
+		let color = self.gram(WIDTH - 1 - x, self.scrolled_row(y));
+		let color = match self.inversion {
+			false => color,
+			true  => Rgb666::new(0x3f - color.r(), 0x3f - color.g(), 0x3f - color.b()),
+		};
+		match self.idle {
+			false => color,
+			true  => Rgb111::new(color.r() & 0x20 != 0, color.g() & 0x20 != 0, color.b() & 0x20 != 0).into(),
+		}
+	}
+
+	/// The whole visible image, as `screen` shows it.
+	pub fn frame(&self) -> Frame {
+		let mut frame = Frame::new(WIDTH, HEIGHT);
+		for y in 0..HEIGHT {
+			for x in 0..WIDTH {
+				frame.set_pixel(x, y, self.screen(x, y).to_rgb888());
+			}
+		}
+		frame
+	}
+
+	pub fn memory_access_control(&self) -> MemoryAccessControl {
+		self.madctl
+	}
+
+	/// Bits per pixel of the MCU interface, from Pixel Format Set.
+	pub fn bits_per_pixel(&self) -> u8 {
+		self.bits_per_pixel
+	}
+
+	/// Top fixed, scrolling and bottom fixed areas.
+	pub fn scroll_definition(&self) -> (u16, u16, u16) {
+		self.scroll_definition
+	}
+
+	/// Vertical Scrolling Start Address, or None outside scrolling mode.
+	pub fn scroll_start(&self) -> Option<u16> {
+		match self.scrolling {
+			false => None,
+			true  => Some(self.scroll_start),
+		}
+	}
+
+	pub fn inversion(&self) -> bool {
+		self.inversion
+	}
+
+	pub fn idle_mode(&self) -> bool {
+		self.idle
+	}
+
+	pub fn display_on(&self) -> bool {
+		self.display_on
+	}
+
+	/// GRAM row shown on screen row `y`.
+	fn scrolled_row(&self, y: u16) -> u16 {
+		let (tfa, vsa, _) = self.scroll_definition;
+		match self.scrolling && vsa > 0 && y >= tfa && y < tfa + vsa {
+			false => y,
+			true  => {
+				let offset = self.scroll_start.saturating_sub(tfa) % vsa;
+				tfa + (y - tfa + offset) % vsa
+			},
+		}
+	}
+
+	/// GRAM column and row for window coordinates `x`, `y`, or None if
+	/// they lie outside the panel in the current orientation.
+	fn memory_address(&self, x: u16, y: u16) -> Option<(u16, u16)> {
+		let (column, row) = match self.madctl.row_column_exchange() {
+			false => (x, y),
+			true  => (y, x),
+		};
+		if column >= WIDTH || row >= HEIGHT {
+			return None;
+		}
+		let column = match self.madctl.column_address_order() {
+			false => column,
+			true  => WIDTH - 1 - column,
+		};
+		let row = match self.madctl.row_address_order() {
+			false => row,
+			true  => HEIGHT - 1 - row,
+		};
+		Some((column, row))
+	}
+
+	/// Step the address counter across the window, wrapping to the next
+	/// page at the end column and back to the start page after the end.
+	fn advance(&mut self) {
+		let (x, y) = self.counter;
+		self.counter = match (x >= self.columns.1, y >= self.pages.1) {
+			(false, _)    => (x + 1, y),
+			(true, false) => (self.columns.0, y + 1),
+			(true, true)  => (self.columns.0, self.pages.0),
+		};
+	}
+
+	fn store(&mut self, color: Rgb666) {
+		let (x, y) = self.counter;
+		if let Some((column, row)) = self.memory_address(x, y) {
+			self.gram[row as usize * WIDTH as usize + column as usize] = color;
+		}
+		self.advance();
+	}
+
+	fn load(&mut self) -> Rgb666 {
+		let (x, y) = self.counter;
+		let color = self.memory_address(x, y)
+			.map_or(Rgb666::BLACK, |(column, row)| self.gram(column, row));
+		self.advance();
+		color
+	}
+
+	fn decode_pixel(&self, pixel: u32) -> Rgb666 {
+		match self.bits_per_pixel {
+			16 => Rgb565::from_bits(pixel as u16).into(),
+			_  => Rgb666::new((pixel >> 12) as u8, (pixel >> 6) as u8, pixel as u8),
+		}
+	}
+}
+
+impl Default for SimulatedPanel {
+	fn default() -> SimulatedPanel {
+		SimulatedPanel::new()
+	}
+}
+
+fn be_u16(data: &[u8], index: usize) -> Option<u16> {
+	Some(((*data.get(index)? as u16) << 8) | *data.get(index + 1)? as u16)
+}
+
+impl Interface for SimulatedPanel {
+	type Error = Infallible;
+
+	fn write_parameters(&mut self, command: u8, data: &[u8]) -> Result<(), Self::Error> {
+		observe_bits_per_pixel(&mut self.bits_per_pixel, command, data);
+		self.read_start = false;
+		match command {
+			0x01 => self.reset(),
+			0x13 => self.scrolling = false,
+			0x20 => self.inversion = false,
+			0x21 => self.inversion = true,
+			0x28 => self.display_on = false,
+			0x29 => self.display_on = true,
+			0x2a => if let (Some(sc), Some(ec)) = (be_u16(data, 0), be_u16(data, 2)) {
+				self.columns = (sc, ec);
+			},
+			0x2b => if let (Some(sp), Some(ep)) = (be_u16(data, 0), be_u16(data, 2)) {
+				self.pages = (sp, ep);
+			},
+			0x2c => self.counter = (self.columns.0, self.pages.0),
+			0x2e => self.read_start = true,
+			0x33 => if let (Some(tfa), Some(vsa), Some(bfa)) = (be_u16(data, 0), be_u16(data, 2), be_u16(data, 4)) {
+				self.scroll_definition = (tfa, vsa, bfa);
+			},
+			0x36 => if let Some(&value) = data.first() {
+				self.madctl = MemoryAccessControl { raw: [value] };
+			},
+			0x37 => if let Some(vsp) = be_u16(data, 0) {
+				self.scroll_start = vsp;
+				self.scrolling = true;
+			},
+			0x38 => self.idle = false,
+			0x39 => self.idle = true,
+			_ => (),
+		}
+		Ok(())
+	}
+
+	fn write_memory<I>(&mut self, iterable: I) -> Result<(), Self::Error>
+		where I: IntoIterator<Item=u32>
+	{
+		for pixel in iterable {
+			let color = self.decode_pixel(pixel);
+			self.store(color);
+		}
+		Ok(())
+	}
+
+	fn read_parameters(&mut self, command: u8, data: &mut [u8]) -> Result<(), Self::Error> {
+		data.fill(0);
+		let value = match command {
+			0x0b => self.madctl.raw[0],
+			0x0c => match self.bits_per_pixel {
+				16 => 0x55,
+				_  => 0x66,
+			},
+			_ => 0,
+		};
+		if let Some(first) = data.first_mut() {
+			*first = value;
+		}
+		Ok(())
+	}
+
+	fn read_memory(&mut self, data: &mut [u32]) -> Result<(), Self::Error> {
+		if self.read_start {
+			self.counter = (self.columns.0, self.pages.0);
+			self.read_start = false;
+		}
+		for value in data.iter_mut() {
+			let color = self.load();
+			*value = ((color.r() as u32) << 18) | ((color.g() as u32) << 10) | ((color.b() as u32) << 2);
+		}
+		Ok(())
+	}
+
+	fn panel_reset(&mut self) -> Result<(), Self::Error> {
+		self.reset();
+		Ok(())
+	}
+}
@@ -0,0 +1,368 @@
+//! Interface implementation for the "4-line serial interface II" bus: an
+//! embedded-hal SPI device carrying command and data bytes, plus a GPIO
+//! driving the D/CX line.
+
+use embedded_hal::digital::OutputPin;
+use embedded_hal::spi::{Operation, SpiDevice};
+
+use crate::{observe_bits_per_pixel, pixel_bytes, Interface, READ_CHUNK_PIXELS};
+
+/// Number of pixels packed into each SPI transfer by `write_repeated`, and
+/// at most by `write_memory`.
+const CHUNK_PIXELS: usize = 32;
+
+/// Errors produced by SpiInterface, wrapping the SPI device or D/CX pin
+/// error that caused them.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SpiError<S, P> {
+	Spi(S),
+	Dc(P),
+}
+
+/// Interface over an embedded-hal `SpiDevice` and a D/CX `OutputPin`.
+///
+/// As with ParallelInterface, the pixel byte ordering follows the COLMOD
+/// DBI format observed passing through.
+///
+/// A memory read must happen in the same chip-select window as its
+/// command, so Memory Read (2Eh) and Read Memory Continue (3Eh) are held
+/// back until the following `read_memory` call. Longer reads are split
+/// into transactions of 32 pixels, each after the first continuing with
+/// Read Memory Continue.
+pub struct SpiInterface<SPI, DC> {
+	spi: SPI,
+	dc: DC,
+	bits_per_pixel: u8,
+	pending_read: Option<u8>,
+}
+
+impl<SPI, DC> SpiInterface<SPI, DC>
+	where SPI: SpiDevice,
+	      DC: OutputPin
+{
+	pub fn new(spi: SPI, dc: DC) -> SpiInterface<SPI, DC> {
+		SpiInterface {
+			spi,
+			dc,
+			bits_per_pixel: 18,
+			pending_read: None,
+		}
+	}
+
+	/// Consume the interface, returning the SPI device and D/CX pin.
+	pub fn release(self) -> (SPI, DC) {
+		(self.spi, self.dc)
+	}
+
+	fn set_dc(&mut self, data: bool) -> Result<(), SpiError<SPI::Error, DC::Error>> {
+		let result = match data {
+			false => self.dc.set_low(),
+			true  => self.dc.set_high(),
+		};
+		result.map_err(SpiError::Dc)
+	}
+
+	fn write(&mut self, data: &[u8]) -> Result<(), SpiError<SPI::Error, DC::Error>> {
+		self.spi.write(data).map_err(SpiError::Spi)
+	}
+}
+
+impl<SPI, DC> Interface for SpiInterface<SPI, DC>
+	where SPI: SpiDevice,
+	      DC: OutputPin
+{
+	type Error = SpiError<SPI::Error, DC::Error>;
+
+	fn write_parameters(&mut self, command: u8, data: &[u8]) -> Result<(), Self::Error> {
+		observe_bits_per_pixel(&mut self.bits_per_pixel, command, data);
+		self.pending_read = None;
+		if data.is_empty() && (command == 0x2e || command == 0x3e) {
+			self.pending_read = Some(command);
+			return Ok(());
+		}
+
+		self.set_dc(false)?;
+		self.write(&[command])?;
+		if !data.is_empty() {
+			self.set_dc(true)?;
+			self.write(data)?;
+		}
+		Ok(())
+	}
+
+	fn write_memory<I>(&mut self, iterable: I) -> Result<(), Self::Error>
+		where I: IntoIterator<Item=u32>
+	{
+		self.set_dc(true)?;
+
+		let mut buffer = [0u8; CHUNK_PIXELS * 3];
+		let mut n = 0;
+		for pixel in iterable {
+			let (bytes, count) = pixel_bytes(pixel, self.bits_per_pixel);
+			buffer[n..n + count].copy_from_slice(&bytes[..count]);
+			n += count;
+			if n + 3 > buffer.len() {
+				self.write(&buffer[..n])?;
+				n = 0;
+			}
+		}
+		if n > 0 {
+			self.write(&buffer[..n])?;
+		}
+		Ok(())
+	}
+
+	fn write_repeated(&mut self, pixel: u32, count: u32) -> Result<(), Self::Error> {
+		self.set_dc(true)?;
+
+		let (buffer, bytes_per_pixel) = repeated_buffer(pixel, self.bits_per_pixel);
+		let (chunks, rest) = (count as usize / CHUNK_PIXELS, count as usize % CHUNK_PIXELS);
+		for _ in 0..chunks {
+			self.write(&buffer[..CHUNK_PIXELS * bytes_per_pixel])?;
+		}
+		if rest > 0 {
+			self.write(&buffer[..rest * bytes_per_pixel])?;
+		}
+		Ok(())
+	}
+
+	fn read_parameters(&mut self, command: u8, data: &mut [u8]) -> Result<(), Self::Error> {
+		self.pending_read = None;
+		self.set_dc(false)?;
+
+		// Multi-byte register reads (RDDID, RDDST, ...) insert a single
+		// dummy clock before the data, so everything arrives one bit late.
+		let mut extra = [0u8; 1];
+		let dummy_clock = data.len() > 1;
+		{
+			let mut operations = [
+				Operation::Write(&[command]),
+				Operation::Read(data),
+				Operation::Read(&mut extra),
+			];
+			let count = if dummy_clock { 3 } else { 2 };
+			self.spi.transaction(&mut operations[..count]).map_err(SpiError::Spi)?;
+		}
+
+		if dummy_clock {
+			shift_out_dummy_clock(data, extra[0]);
+		}
+		Ok(())
+	}
+
+	fn read_memory(&mut self, data: &mut [u32]) -> Result<(), Self::Error> {
+		let mut command = self.pending_read.take().unwrap_or(0x3e);
+		self.set_dc(false)?;
+
+		let mut buffer = [0u8; READ_CHUNK_PIXELS * 3];
+		for chunk in data.chunks_mut(READ_CHUNK_PIXELS) {
+			let bytes = &mut buffer[..chunk.len() * 3];
+			let mut dummy = [0u8; 1];
+			let mut operations = [
+				Operation::Write(&[command]),
+				Operation::Read(&mut dummy),
+				Operation::Read(bytes),
+			];
+			self.spi.transaction(&mut operations).map_err(SpiError::Spi)?;
+			unpack_pixels(&buffer, chunk);
+			command = 0x3e;
+		}
+		Ok(())
+	}
+
+	fn panel_reset(&mut self) -> Result<(), Self::Error> {
+		self.bits_per_pixel = 18;
+		self.pending_read = None;
+		Ok(())
+	}
+}
+
+/// A transfer buffer filled with copies of one pixel, and the number of
+/// bytes each copy takes.
+fn repeated_buffer(pixel: u32, bits_per_pixel: u8) -> ([u8; CHUNK_PIXELS * 3], usize) {
+	let (bytes, count) = pixel_bytes(pixel, bits_per_pixel);
+	let mut buffer = [0u8; CHUNK_PIXELS * 3];
+	for chunk in buffer.chunks_exact_mut(count) {
+		chunk.copy_from_slice(&bytes[..count]);
+	}
+	(buffer, count)
+}
+
+/// Undo the single dummy clock that precedes multi-byte register reads,
+/// pulling the missing low bit of the last byte from `extra`.
+fn shift_out_dummy_clock(data: &mut [u8], extra: u8) {
+	let mut next = extra;
+	for byte in data.iter_mut().rev() {
+		let shifted = (*byte << 1) | (next >> 7);
+		next = *byte;
+		*byte = shifted;
+	}
+}
+
+/// Unpack three-byte pixels from `bytes` into 0x00RRGGBB words.
+fn unpack_pixels(bytes: &[u8], data: &mut [u32]) {
+	for (word, pixel) in data.iter_mut().zip(bytes.chunks_exact(3)) {
+		*word = ((pixel[0] as u32) << 16) | ((pixel[1] as u32) << 8) | (pixel[2] as u32);
+	}
+}
+
+#[cfg(feature = "async")]
+pub use self::asynch::AsyncSpiInterface;
+
+#[cfg(feature = "async")]
+mod asynch {
+	use embedded_hal::digital::OutputPin;
+	use embedded_hal_async::spi::{Operation, SpiDevice};
+
+	use crate::asynch::AsyncInterface;
+	use crate::{observe_bits_per_pixel, pixel_bytes, READ_CHUNK_PIXELS};
+	use super::{repeated_buffer, shift_out_dummy_clock, unpack_pixels, SpiError, CHUNK_PIXELS};
+
+	/// AsyncInterface over an embedded-hal-async `SpiDevice` and a D/CX
+	/// `OutputPin`, with the same framing as SpiInterface.
+	pub struct AsyncSpiInterface<SPI, DC> {
+		spi: SPI,
+		dc: DC,
+		bits_per_pixel: u8,
+		pending_read: Option<u8>,
+	}
+
+	impl<SPI, DC> AsyncSpiInterface<SPI, DC>
+		where SPI: SpiDevice,
+		      DC: OutputPin
+	{
+		pub fn new(spi: SPI, dc: DC) -> AsyncSpiInterface<SPI, DC> {
+			AsyncSpiInterface {
+				spi,
+				dc,
+				bits_per_pixel: 18,
+				pending_read: None,
+			}
+		}
+
+		/// Consume the interface, returning the SPI device and D/CX pin.
+		pub fn release(self) -> (SPI, DC) {
+			(self.spi, self.dc)
+		}
+
+		fn set_dc(&mut self, data: bool) -> Result<(), SpiError<SPI::Error, DC::Error>> {
+			let result = match data {
+				false => self.dc.set_low(),
+				true  => self.dc.set_high(),
+			};
+			result.map_err(SpiError::Dc)
+		}
+
+		async fn write(&mut self, data: &[u8]) -> Result<(), SpiError<SPI::Error, DC::Error>> {
+			self.spi.write(data).await.map_err(SpiError::Spi)
+		}
+	}
+
+	impl<SPI, DC> AsyncInterface for AsyncSpiInterface<SPI, DC>
+		where SPI: SpiDevice,
+		      DC: OutputPin
+	{
+		type Error = SpiError<SPI::Error, DC::Error>;
+
+		async fn write_parameters(&mut self, command: u8, data: &[u8]) -> Result<(), Self::Error> {
+			observe_bits_per_pixel(&mut self.bits_per_pixel, command, data);
+			self.pending_read = None;
+			if data.is_empty() && (command == 0x2e || command == 0x3e) {
+				self.pending_read = Some(command);
+				return Ok(());
+			}
+
+			self.set_dc(false)?;
+			self.write(&[command]).await?;
+			if !data.is_empty() {
+				self.set_dc(true)?;
+				self.write(data).await?;
+			}
+			Ok(())
+		}
+
+		async fn write_memory<I>(&mut self, iterable: I) -> Result<(), Self::Error>
+			where I: IntoIterator<Item=u32>
+		{
+			self.set_dc(true)?;
+
+			let mut buffer = [0u8; CHUNK_PIXELS * 3];
+			let mut n = 0;
+			for pixel in iterable {
+				let (bytes, count) = pixel_bytes(pixel, self.bits_per_pixel);
+				buffer[n..n + count].copy_from_slice(&bytes[..count]);
+				n += count;
+				if n + 3 > buffer.len() {
+					self.write(&buffer[..n]).await?;
+					n = 0;
+				}
+			}
+			if n > 0 {
+				self.write(&buffer[..n]).await?;
+			}
+			Ok(())
+		}
+
+		async fn write_repeated(&mut self, pixel: u32, count: u32) -> Result<(), Self::Error> {
+			self.set_dc(true)?;
+
+			let (buffer, bytes_per_pixel) = repeated_buffer(pixel, self.bits_per_pixel);
+			let (chunks, rest) = (count as usize / CHUNK_PIXELS, count as usize % CHUNK_PIXELS);
+			for _ in 0..chunks {
+				self.write(&buffer[..CHUNK_PIXELS * bytes_per_pixel]).await?;
+			}
+			if rest > 0 {
+				self.write(&buffer[..rest * bytes_per_pixel]).await?;
+			}
+			Ok(())
+		}
+
+		async fn read_parameters(&mut self, command: u8, data: &mut [u8]) -> Result<(), Self::Error> {
+			self.pending_read = None;
+			self.set_dc(false)?;
+
+			let mut extra = [0u8; 1];
+			let dummy_clock = data.len() > 1;
+			{
+				let mut operations = [
+					Operation::Write(&[command]),
+					Operation::Read(data),
+					Operation::Read(&mut extra),
+				];
+				let count = if dummy_clock { 3 } else { 2 };
+				self.spi.transaction(&mut operations[..count]).await.map_err(SpiError::Spi)?;
+			}
+
+			if dummy_clock {
+				shift_out_dummy_clock(data, extra[0]);
+			}
+			Ok(())
+		}
+
+		async fn read_memory(&mut self, data: &mut [u32]) -> Result<(), Self::Error> {
+			let mut command = self.pending_read.take().unwrap_or(0x3e);
+			self.set_dc(false)?;
+
+			let mut buffer = [0u8; READ_CHUNK_PIXELS * 3];
+			for chunk in data.chunks_mut(READ_CHUNK_PIXELS) {
+				let bytes = &mut buffer[..chunk.len() * 3];
+				let mut dummy = [0u8; 1];
+				let mut operations = [
+					Operation::Write(&[command]),
+					Operation::Read(&mut dummy),
+					Operation::Read(bytes),
+				];
+				self.spi.transaction(&mut operations).await.map_err(SpiError::Spi)?;
+				unpack_pixels(&buffer, chunk);
+				command = 0x3e;
+			}
+			Ok(())
+		}
+
+		async fn panel_reset(&mut self) -> Result<(), Self::Error> {
+			self.bits_per_pixel = 18;
+			self.pending_read = None;
+			Ok(())
+		}
+	}
+}
@@ -0,0 +1,215 @@
+//! Scrolling text terminal: fixed-width glyphs drawn into the scrolling
+//! area, which is advanced with hardware scrolling instead of redrawn.
+
+use core::fmt;
+use core::ops::Range;
+
+use crate::{Color, Controller, Error, Interface, Rect, ResetPin, ScrollRegion, WIDTH};
+
+/// A fixed-width bitmap font covering a contiguous range of characters.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Font {
+	data: &'static [u8],
+	width: u16,
+	height: u16,
+	first: char,
+}
+
+impl Font {
+	/// Glyphs of `width` x `height` pixels, stored consecutively in `data`
+	/// starting with `first`. Each glyph is `height` rows of
+	/// `(width + 7) / 8` bytes, with the most significant bit leftmost.
+	pub const fn new(data: &'static [u8], width: u16, height: u16, first: char) -> Font {
+		Font {
+			data,
+			width,
+			height,
+			first,
+		}
+	}
+
+	pub fn width(&self) -> u16 {
+		self.width
+	}
+
+	pub fn height(&self) -> u16 {
+		self.height
+	}
+
+	fn row_bytes(&self) -> usize {
+		(self.width as usize).div_ceil(8)
+	}
+
+	/// Bitmap for `c`, or None if the font does not cover it.
+	fn glyph(&self, c: char) -> Option<&'static [u8]> {
+		let len = self.row_bytes() * self.height as usize;
+		let index = (c as u32).checked_sub(self.first as u32)? as usize;
+		self.data.get(index * len..(index + 1) * len)
+	}
+
+	fn pixel(&self, glyph: &[u8], x: u16, y: u16) -> bool {
+		let byte = glyph[y as usize * self.row_bytes() + x as usize / 8];
+		byte & (0x80 >> (x % 8)) != 0
+	}
+}
+
+/// Text console on the scrolling area of a ScrollRegion.
+///
+/// Text wraps at the panel width; a new line at the bottom scrolls the
+/// area up by one line with Vertical Scrolling Start Address and clears
+/// only the exposed rows. Lines are laid out along GRAM rows, so the panel
+/// should be in `Orientation::Portrait`.
+///
+/// `core::fmt::Write` is implemented for use with `write!`; it reports
+/// Interface failures as `fmt::Error`, while `print` returns them.
+pub struct Terminal<'a, T, RST, C>
+	where T: Interface
+{
+	controller: &'a mut Controller<T, RST>,
+	font: Font,
+	region: ScrollRegion,
+	foreground: C,
+	background: C,
+	column: u16,
+	line: u16,
+}
+
+impl<'a, T: Interface, RST: ResetPin, C: Color> Terminal<'a, T, RST, C> {
+	/// Define `region` on the panel and clear its scrolling area. Fails
+	/// with `Error::OutOfBounds` if a glyph does not fit the area.
+	pub fn new(controller: &'a mut Controller<T, RST>, font: Font, region: ScrollRegion, foreground: C, background: C) -> Result<Self, Error<T::Error, RST::Error>> {
+		if font.width == 0 || font.width > WIDTH || font.height == 0 || font.height > region.scroll_height() {
+			return Err(Error::OutOfBounds);
+		}
+		controller.set_scroll_region(&region)?;
+		let mut terminal = Terminal {
+			controller,
+			font,
+			region,
+			foreground,
+			background,
+			column: 0,
+			line: 0,
+		};
+		terminal.clear_rows(0..region.scroll_height())?;
+		Ok(terminal)
+	}
+
+	/// The region, with its current scroll offset.
+	pub fn region(&self) -> ScrollRegion {
+		self.region
+	}
+
+	pub fn columns(&self) -> u16 {
+		WIDTH / self.font.width
+	}
+
+	pub fn lines(&self) -> u16 {
+		self.region.scroll_height() / self.font.height
+	}
+
+	/// Cursor column and line.
+	pub fn cursor(&self) -> (u16, u16) {
+		(self.column, self.line)
+	}
+
+	pub fn set_colors(&mut self, foreground: C, background: C) {
+		self.foreground = foreground;
+		self.background = background;
+	}
+
+	/// Write `text`, handling `\n` (new line) and `\r` (start of line).
+	/// Characters the font lacks are drawn blank.
+	pub fn print(&mut self, text: &str) -> Result<(), Error<T::Error, RST::Error>> {
+		for c in text.chars() {
+			match c {
+				'\n' => self.new_line()?,
+				'\r' => self.column = 0,
+				c => {
+					if self.column == self.columns() {
+						self.new_line()?;
+					}
+					self.draw_glyph(c)?;
+					self.column += 1;
+				},
+			}
+		}
+		Ok(())
+	}
+
+	/// Clear the scrolling area and return the cursor to the top.
+	pub fn clear(&mut self) -> Result<(), Error<T::Error, RST::Error>> {
+		self.column = 0;
+		self.line = 0;
+		self.clear_rows(0..self.region.scroll_height())
+	}
+
+	fn new_line(&mut self) -> Result<(), Error<T::Error, RST::Error>> {
+		self.column = 0;
+		match self.line + 1 < self.lines() {
+			true => {
+				self.line += 1;
+				Ok(())
+			},
+			false => {
+				self.controller.scroll_by(&mut self.region, self.font.height as i32)?;
+				// The last line, and any rows left below it by a scrolling
+				// area that is not a whole number of lines.
+				self.clear_rows(self.line * self.font.height..self.region.scroll_height())
+			},
+		}
+	}
+
+	/// Split `rows` of the scrolling area, counted from the top of the
+	/// area as shown, into the GRAM row ranges holding them: two if they
+	/// wrap around the end of the area.
+	fn physical_rows(&self, rows: Range<u16>) -> (Range<u16>, Range<u16>) {
+		let top = self.region.top_fixed();
+		let height = self.region.scroll_height();
+		let start = (rows.start + self.region.offset()) % height;
+		let len = rows.end - rows.start;
+		let first = len.min(height - start);
+		(top + start..top + start + first, top..top + len - first)
+	}
+
+	fn clear_rows(&mut self, rows: Range<u16>) -> Result<(), Error<T::Error, RST::Error>> {
+		let (first, second) = self.physical_rows(rows);
+		for rows in [first, second] {
+			if !rows.is_empty() {
+				self.controller.fill_rect(Rect::new(0, rows.start, WIDTH, rows.end - rows.start), self.background)?;
+			}
+		}
+		Ok(())
+	}
+
+	fn draw_glyph(&mut self, c: char) -> Result<(), Error<T::Error, RST::Error>> {
+		let font = self.font;
+		let glyph = font.glyph(c);
+		let (foreground, background) = (self.foreground, self.background);
+		let x = self.column * font.width;
+		let top = self.line * font.height;
+		let (first, second) = self.physical_rows(top..top + font.height);
+		let mut glyph_y = 0;
+		for rows in [first, second] {
+			if rows.is_empty() {
+				continue;
+			}
+			let height = rows.end - rows.start;
+			let glyph_rows = glyph_y..glyph_y + height;
+			let pixels = glyph_rows.flat_map(|y| (0..font.width).map(move |x| (x, y)))
+				.map(|(x, y)| match glyph.is_some_and(|g| font.pixel(g, x, y)) {
+					false => background,
+					true  => foreground,
+				});
+			self.controller.write_window(Rect::new(x, rows.start, font.width, height), pixels)?;
+			glyph_y += height;
+		}
+		Ok(())
+	}
+}
+
+impl<T: Interface, RST: ResetPin, C: Color> fmt::Write for Terminal<'_, T, RST, C> {
+	fn write_str(&mut self, s: &str) -> fmt::Result {
+		self.print(s).map_err(|_| fmt::Error)
+	}
+}
@@ -0,0 +1,220 @@
+//! Interface implementation for "3-line serial interface I", where each
+//! byte is sent as a 9-bit frame whose first bit is the D/CX flag and no
+//! separate D/CX pin is used.
+
+use crate::{observe_bits_per_pixel, pixel_bytes, Interface};
+
+/// Bit-level access to a 3-line serial bus: CSX, SCL and SDA (or SDI/SDO
+/// when the module brings them out separately). Bits are clocked MSB
+/// first. Implementations may be bit-banged or backed by a peripheral
+/// that supports 9-bit words.
+pub trait SerialBus {
+	type Error;
+
+	/// Assert (`true`) or release (`false`) CSX.
+	fn select(&mut self, selected: bool) -> Result<(), Self::Error>;
+	/// Clock out the low `bits` bits of `value`.
+	fn write_bits(&mut self, value: u16, bits: u8) -> Result<(), Self::Error>;
+	/// Clock in `bits` bits, returned right-aligned.
+	fn read_bits(&mut self, bits: u8) -> Result<u16, Self::Error>;
+}
+
+/// Interface over a 3-line, 9-bit serial bus.
+///
+/// As with ParallelInterface, the pixel byte ordering follows the COLMOD
+/// DBI format observed passing through. Memory Read (2Eh) and Read Memory
+/// Continue (3Eh) are held back until the following `read_memory` call,
+/// since the data must be read within the same CSX window.
+pub struct ThreeWireInterface<B> {
+	bus: B,
+	bits_per_pixel: u8,
+	pending_read: Option<u8>,
+}
+
+impl<B> ThreeWireInterface<B>
+	where B: SerialBus
+{
+	pub fn new(bus: B) -> ThreeWireInterface<B> {
+		ThreeWireInterface {
+			bus,
+			bits_per_pixel: 18,
+			pending_read: None,
+		}
+	}
+
+	/// Consume the interface, returning the underlying bus.
+	pub fn release(self) -> B {
+		self.bus
+	}
+
+	fn write_command_frame(&mut self, command: u8) -> Result<(), B::Error> {
+		self.bus.write_bits(command as u16, 9)
+	}
+
+	fn write_data_frame(&mut self, data: u8) -> Result<(), B::Error> {
+		self.bus.write_bits(0x100 | data as u16, 9)
+	}
+}
+
+impl<B> Interface for ThreeWireInterface<B>
+	where B: SerialBus
+{
+	type Error = B::Error;
+
+	fn write_parameters(&mut self, command: u8, data: &[u8]) -> Result<(), Self::Error> {
+		observe_bits_per_pixel(&mut self.bits_per_pixel, command, data);
+		self.pending_read = None;
+		if data.is_empty() && (command == 0x2e || command == 0x3e) {
+			self.pending_read = Some(command);
+			return Ok(());
+		}
+
+		self.bus.select(true)?;
+		self.write_command_frame(command)?;
+		for &value in data {
+			self.write_data_frame(value)?;
+		}
+		self.bus.select(false)
+	}
+
+	fn write_memory<I>(&mut self, iterable: I) -> Result<(), Self::Error>
+		where I: IntoIterator<Item=u32>
+	{
+		self.bus.select(true)?;
+		for pixel in iterable {
+			let (bytes, count) = pixel_bytes(pixel, self.bits_per_pixel);
+			for &byte in &bytes[..count] {
+				self.write_data_frame(byte)?;
+			}
+		}
+		self.bus.select(false)
+	}
+
+	fn read_parameters(&mut self, command: u8, data: &mut [u8]) -> Result<(), Self::Error> {
+		self.pending_read = None;
+		self.bus.select(true)?;
+		self.write_command_frame(command)?;
+
+		// Multi-byte register reads (RDDID, RDDST, ...) insert a single
+		// dummy clock before the data.
+		if data.len() > 1 {
+			self.bus.read_bits(1)?;
+		}
+		for value in data.iter_mut() {
+			*value = self.bus.read_bits(8)? as u8;
+		}
+		self.bus.select(false)
+	}
+
+	fn read_memory(&mut self, data: &mut [u32]) -> Result<(), Self::Error> {
+		let command = self.pending_read.take().unwrap_or(0x3e);
+		self.bus.select(true)?;
+		self.write_command_frame(command)?;
+
+		// Memory reads start with a dummy byte, then three bytes per pixel.
+		self.bus.read_bits(8)?;
+		for pixel in data.iter_mut() {
+			let high = self.bus.read_bits(8)? as u32;
+			let low = self.bus.read_bits(16)? as u32;
+			*pixel = (high << 16) | low;
+		}
+		self.bus.select(false)
+	}
+
+	fn panel_reset(&mut self) -> Result<(), Self::Error> {
+		self.bits_per_pixel = 18;
+		self.pending_read = None;
+		Ok(())
+	}
+}
+
+#[cfg(feature = "embedded-hal")]
+pub use self::bitbang::{BitBangBus, BitBangError};
+
+#[cfg(feature = "embedded-hal")]
+mod bitbang {
+	use embedded_hal::digital::{InputPin, OutputPin};
+
+	use super::SerialBus;
+
+	/// Errors produced by BitBangBus, naming the pin that failed.
+	#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+	pub enum BitBangError<CS, SCL, SDI, SDO> {
+		Cs(CS),
+		Scl(SCL),
+		Sdi(SDI),
+		Sdo(SDO),
+	}
+
+	/// SerialBus bit-banged over embedded-hal GPIOs, using separate SDI
+	/// (output) and SDO (input) pins. Data is driven on the falling edge
+	/// of SCL and sampled by both sides on the rising edge.
+	pub struct BitBangBus<CS, SCL, SDI, SDO> {
+		cs: CS,
+		scl: SCL,
+		sdi: SDI,
+		sdo: SDO,
+	}
+
+	impl<CS, SCL, SDI, SDO> BitBangBus<CS, SCL, SDI, SDO>
+		where CS: OutputPin,
+		      SCL: OutputPin,
+		      SDI: OutputPin,
+		      SDO: InputPin
+	{
+		pub fn new(cs: CS, scl: SCL, sdi: SDI, sdo: SDO) -> BitBangBus<CS, SCL, SDI, SDO> {
+			BitBangBus {
+				cs,
+				scl,
+				sdi,
+				sdo,
+			}
+		}
+
+		/// Consume the bus, returning the pins.
+		pub fn release(self) -> (CS, SCL, SDI, SDO) {
+			(self.cs, self.scl, self.sdi, self.sdo)
+		}
+	}
+
+	impl<CS, SCL, SDI, SDO> SerialBus for BitBangBus<CS, SCL, SDI, SDO>
+		where CS: OutputPin,
+		      SCL: OutputPin,
+		      SDI: OutputPin,
+		      SDO: InputPin
+	{
+		type Error = BitBangError<CS::Error, SCL::Error, SDI::Error, SDO::Error>;
+
+		fn select(&mut self, selected: bool) -> Result<(), Self::Error> {
+			let result = match selected {
+				false => self.cs.set_high(),
+				true  => self.cs.set_low(),
+			};
+			result.map_err(BitBangError::Cs)
+		}
+
+		fn write_bits(&mut self, value: u16, bits: u8) -> Result<(), Self::Error> {
+			for bit in (0..bits).rev() {
+				self.scl.set_low().map_err(BitBangError::Scl)?;
+				let result = match (value >> bit) & 1 {
+					0 => self.sdi.set_low(),
+					_ => self.sdi.set_high(),
+				};
+				result.map_err(BitBangError::Sdi)?;
+				self.scl.set_high().map_err(BitBangError::Scl)?;
+			}
+			Ok(())
+		}
+
+		fn read_bits(&mut self, bits: u8) -> Result<u16, Self::Error> {
+			let mut value = 0u16;
+			for _ in 0..bits {
+				self.scl.set_low().map_err(BitBangError::Scl)?;
+				self.scl.set_high().map_err(BitBangError::Scl)?;
+				let high = self.sdo.is_high().map_err(BitBangError::Sdo)?;
+				value = (value << 1) | high as u16;
+			}
+			Ok(value)
+		}
+	}
+}
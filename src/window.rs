@@ -0,0 +1,167 @@
+//! Address windows: Column Address Set, Page Address Set and Memory Write
+//! issued together for a rectangle, checked against the panel dimensions
+//! in the current orientation.
+
+#[cfg(feature = "async")]
+use crate::asynch::{AsyncController, AsyncInterface};
+use crate::{
+	Color, Controller, Error, Interface, MemoryAccessControl, ResetPin, HEIGHT,
+	READ_CHUNK_PIXELS, WIDTH,
+};
+
+/// A rectangle of pixels, in the coordinates of the current orientation.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Rect {
+	pub x: u16,
+	pub y: u16,
+	pub width: u16,
+	pub height: u16,
+}
+
+impl Rect {
+	pub fn new(x: u16, y: u16, width: u16, height: u16) -> Rect {
+		Rect {
+			x,
+			y,
+			width,
+			height,
+		}
+	}
+
+	/// Number of pixels covered.
+	pub fn area(&self) -> u32 {
+		self.width as u32 * self.height as u32
+	}
+
+	/// Inclusive end column, or None if the rectangle is empty.
+	fn end_x(&self) -> Option<u16> {
+		self.width.checked_sub(1).and_then(|w| self.x.checked_add(w))
+	}
+
+	/// Inclusive end row, or None if the rectangle is empty.
+	fn end_y(&self) -> Option<u16> {
+		self.height.checked_sub(1).and_then(|h| self.y.checked_add(h))
+	}
+}
+
+/// Width and height of the panel under `madctl`: row/column exchange
+/// turns the portrait panel on its side.
+pub(crate) fn dimensions(madctl: MemoryAccessControl) -> (u16, u16) {
+	match madctl.row_column_exchange() {
+		false => (WIDTH, HEIGHT),
+		true  => (HEIGHT, WIDTH),
+	}
+}
+
+/// Inclusive column and page address ranges for `rect`, or None if it is
+/// empty or extends past a panel of `width` x `height`.
+fn address_ranges(rect: Rect, (width, height): (u16, u16)) -> Option<((u16, u16), (u16, u16))> {
+	match (rect.end_x(), rect.end_y()) {
+		(Some(ex), Some(ey)) if ex < width && ey < height => Some(((rect.x, ex), (rect.y, ey))),
+		_ => None,
+	}
+}
+
+impl<T: Interface, RST: ResetPin> Controller<T, RST> {
+	/// Width and height of the panel in the orientation last set with
+	/// `memory_access_control`.
+	pub fn dimensions(&self) -> (u16, u16) {
+		dimensions(self.madctl)
+	}
+
+	/// Set the column and page address ranges to `rect`.
+	pub fn set_window(&mut self, rect: Rect) -> Result<(), Error<T::Error, RST::Error>> {
+		let ((sc, ec), (sp, ep)) = address_ranges(rect, self.dimensions()).ok_or(Error::OutOfBounds)?;
+		self.column_address_set(sc, ec)?;
+		self.page_address_set(sp, ep)
+	}
+
+	/// Set the window to `rect` and write `pixels` into it, row by row.
+	pub fn write_window<C, I>(&mut self, rect: Rect, pixels: I) -> Result<(), Error<T::Error, RST::Error>>
+		where C: Color,
+		      I: IntoIterator<Item=C>
+	{
+		self.set_window(rect)?;
+		self.memory_write_start()?;
+		self.write_memory(pixels)
+	}
+
+	/// Fill `rect` with a single color.
+	pub fn fill_rect<C: Color>(&mut self, rect: Rect, color: C) -> Result<(), Error<T::Error, RST::Error>> {
+		self.set_window(rect)?;
+		self.memory_write_start()?;
+		self.iface.write_repeated(color.to_raw(self.format), rect.area())?;
+		Ok(())
+	}
+
+	/// Fill the whole panel with a single color.
+	pub fn clear<C: Color>(&mut self, color: C) -> Result<(), Error<T::Error, RST::Error>> {
+		let (width, height) = self.dimensions();
+		self.fill_rect(Rect::new(0, 0, width, height), color)
+	}
+
+	/// Read `rect` back from GRAM, row by row, into the start of `data`.
+	/// The panel returns 18-bit samples whatever the pixel format; they are
+	/// converted to `C`. Large windows are read in chunks, each after a
+	/// Read Memory Continue. Fails with `Error::OutOfBounds` if `data` is
+	/// shorter than `rect.area()`.
+	pub fn read_rect<C: Color>(&mut self, rect: Rect, data: &mut [C]) -> Result<(), Error<T::Error, RST::Error>> {
+		let data = data.get_mut(..rect.area() as usize).ok_or(Error::OutOfBounds)?;
+		self.set_window(rect)?;
+		for (i, chunk) in data.chunks_mut(READ_CHUNK_PIXELS).enumerate() {
+			match i {
+				0 => self.memory_read_start()?,
+				_ => self.read_memory_continue()?,
+			}
+			self.read_memory(chunk)?;
+		}
+		Ok(())
+	}
+}
+
+#[cfg(feature = "async")]
+impl<T: AsyncInterface, RST: ResetPin> AsyncController<T, RST> {
+	pub fn dimensions(&self) -> (u16, u16) {
+		dimensions(self.madctl)
+	}
+
+	pub async fn set_window(&mut self, rect: Rect) -> Result<(), Error<T::Error, RST::Error>> {
+		let ((sc, ec), (sp, ep)) = address_ranges(rect, self.dimensions()).ok_or(Error::OutOfBounds)?;
+		self.column_address_set(sc, ec).await?;
+		self.page_address_set(sp, ep).await
+	}
+
+	pub async fn write_window<C, I>(&mut self, rect: Rect, pixels: I) -> Result<(), Error<T::Error, RST::Error>>
+		where C: Color,
+		      I: IntoIterator<Item=C>
+	{
+		self.set_window(rect).await?;
+		self.memory_write_start().await?;
+		self.write_memory(pixels).await
+	}
+
+	pub async fn fill_rect<C: Color>(&mut self, rect: Rect, color: C) -> Result<(), Error<T::Error, RST::Error>> {
+		self.set_window(rect).await?;
+		self.memory_write_start().await?;
+		self.iface.write_repeated(color.to_raw(self.format), rect.area()).await?;
+		Ok(())
+	}
+
+	pub async fn clear<C: Color>(&mut self, color: C) -> Result<(), Error<T::Error, RST::Error>> {
+		let (width, height) = self.dimensions();
+		self.fill_rect(Rect::new(0, 0, width, height), color).await
+	}
+
+	pub async fn read_rect<C: Color>(&mut self, rect: Rect, data: &mut [C]) -> Result<(), Error<T::Error, RST::Error>> {
+		let data = data.get_mut(..rect.area() as usize).ok_or(Error::OutOfBounds)?;
+		self.set_window(rect).await?;
+		for (i, chunk) in data.chunks_mut(READ_CHUNK_PIXELS).enumerate() {
+			match i {
+				0 => self.memory_read_start().await?,
+				_ => self.read_memory_continue().await?,
+			}
+			self.read_memory(chunk).await?;
+		}
+		Ok(())
+	}
+}
@@ -0,0 +1,47 @@
+//! Command stream decoder tests.
+
+use lcd_ili9341::{decode_stream, Command, PowerControl1, VcomControl1};
+
+fn decode(bytes: &[(bool, u8)]) -> Vec<Command> {
+	decode_stream(bytes.iter().copied()).collect()
+}
+
+#[test]
+fn power_and_vcom_registers_format_every_value() {
+	for a in 0..=0xff {
+		for command in [0xc0, 0xc7] {
+			for decoded in decode(&[(true, command), (false, a)]) {
+				assert!(!decoded.to_string().is_empty());
+			}
+		}
+		for b in 0..=0xff {
+			for decoded in decode(&[(true, 0xc5), (false, a), (false, b)]) {
+				assert!(!decoded.to_string().is_empty());
+			}
+		}
+	}
+}
+
+#[test]
+fn reserved_power_and_vcom_values_print_raw() {
+	let decoded = decode(&[
+		(true, 0xc0), (false, 0x00),
+		(true, 0xc0), (false, 0x21),
+		(true, 0xc0), (false, 0xff),
+		(true, 0xc5), (false, 0x31), (false, 0x3c),
+		(true, 0xc5), (false, 0x65), (false, 0x00),
+	]);
+	let text: Vec<String> = decoded.iter().map(|c| c.to_string()).collect();
+	assert_eq!(text, [
+		"PWCTRL1 [00]",
+		"PWCTRL1 gvdd=4500mV",
+		"PWCTRL1 gvdd=6000mV",
+		"VMCTRL1 vcomh=3925mV vcoml=-1000mV",
+		"VMCTRL1 [65, 00]",
+	]);
+
+	assert_eq!(PowerControl1::default().gvdd_millivolts(), Some(4500));
+	assert_eq!(PowerControl1::new(3000).unwrap().gvdd_millivolts(), Some(3000));
+	assert_eq!(VcomControl1::new(5200, 0).unwrap().vcomh_millivolts(), Some(5200));
+	assert_eq!(VcomControl1::new(5200, 0).unwrap().vcoml_millivolts(), Some(0));
+}
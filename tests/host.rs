@@ -0,0 +1,363 @@
+//! Host-side tests driving a Controller through the simulated panel, the
+//! recording interface and command scripts.
+//!
+//! These need the `std` and `embedded-hal` features; run them with
+//! `cargo test --all-features`. Without those features this file builds to
+//! an empty test binary.
+
+#![cfg(all(feature = "std", feature = "embedded-hal"))]
+
+use core::fmt::Write;
+
+use lcd_ili9341::*;
+
+struct NoDelay;
+
+impl embedded_hal::delay::DelayNs for NoDelay {
+	fn delay_ns(&mut self, _ns: u32) {}
+}
+
+/// RESX pin whose `set_low` fails when `stuck`.
+struct Resx {
+	stuck: bool,
+}
+
+#[derive(Debug, PartialEq)]
+struct PinFault;
+
+impl embedded_hal::digital::Error for PinFault {
+	fn kind(&self) -> embedded_hal::digital::ErrorKind {
+		embedded_hal::digital::ErrorKind::Other
+	}
+}
+
+impl embedded_hal::digital::ErrorType for Resx {
+	type Error = PinFault;
+}
+
+impl embedded_hal::digital::OutputPin for Resx {
+	fn set_low(&mut self) -> Result<(), PinFault> {
+		match self.stuck {
+			false => Ok(()),
+			true  => Err(PinFault),
+		}
+	}
+
+	fn set_high(&mut self) -> Result<(), PinFault> {
+		Ok(())
+	}
+}
+
+const RED: Rgb565 = Rgb565::from_rgb888(0xff, 0x00, 0x00);
+const BLUE: Rgb565 = Rgb565::from_rgb888(0x00, 0x00, 0xff);
+
+/// A panel after the default power-on sequence: portrait, 16 bits per
+/// pixel, display on.
+fn initialized() -> Controller<SimulatedPanel> {
+	let mut controller = Controller::new(SimulatedPanel::new());
+	controller.init(&mut NoDelay, &InitConfig::new()).unwrap();
+	controller
+}
+
+fn rgb888(color: Rgb565) -> [u8; 3] {
+	Rgb666::from(color).to_rgb888()
+}
+
+/// The bus traffic of `transactions`, as `decode_stream` takes it.
+fn bus_bytes(transactions: &[Transaction]) -> Vec<(bool, u8)> {
+	let mut bytes = Vec::new();
+	for transaction in transactions {
+		match *transaction {
+			Transaction::Command { command, ref parameters } => {
+				bytes.push((true, command));
+				bytes.extend(parameters.iter().map(|&byte| (false, byte)));
+			},
+			Transaction::WriteMemory(ref pixels) =>
+				bytes.extend(pixels.iter().flat_map(|&pixel| (pixel as u16).to_be_bytes()).map(|byte| (false, byte))),
+			_ => panic!("unexpected {}", transaction),
+		}
+	}
+	bytes
+}
+
+#[test]
+fn hard_reset_restores_the_pixel_format_everywhere() {
+	let mut controller = Controller::with_reset_pin(SimulatedPanel::new(), Resx { stuck: false });
+	controller.pixel_format_set(PixelFormat::new(BitsPerPixel::Sixteen, BitsPerPixel::Sixteen)).unwrap();
+	controller.hard_reset(&mut NoDelay).unwrap();
+	controller.fill_rect(Rect::new(0, 0, 2, 1), Rgb666::new(0x3f, 0x01, 0x20)).unwrap();
+	let (panel, _) = controller.release();
+
+	assert_eq!(panel.bits_per_pixel(), 18);
+	assert_eq!(panel.gram(0, 0), Rgb666::new(0x3f, 0x01, 0x20));
+	assert_eq!(panel.gram(1, 0), Rgb666::new(0x3f, 0x01, 0x20));
+}
+
+/// Bring-up as an application writes it, generic over the RESX pin.
+fn bring_up<T, P>(controller: &mut Controller<T, P>) -> Result<(), Error<T::Error, P::Error>>
+	where T: Interface,
+	      P: embedded_hal::digital::OutputPin
+{
+	controller.hard_reset(&mut NoDelay)?;
+	controller.init(&mut NoDelay, &InitConfig::new())?;
+	controller.clear(Rgb565::WHITE)?;
+	Ok(())
+}
+
+#[test]
+fn hard_reset_and_init_share_an_error_type() {
+	let mut controller = Controller::with_reset_pin(SimulatedPanel::new(), Resx { stuck: false });
+	bring_up(&mut controller).unwrap();
+	let (panel, _) = controller.release();
+	assert!(panel.display_on());
+	assert_eq!(panel.pixel(0, 0), Rgb666::from(Rgb565::WHITE));
+
+	let mut controller = Controller::with_reset_pin(SimulatedPanel::new(), Resx { stuck: true });
+	assert_eq!(bring_up(&mut controller), Err(Error::ResetPin(PinFault)));
+}
+
+#[test]
+fn read_rect_returns_what_was_written() {
+	let mut controller = initialized();
+	let rect = Rect::new(3, 4, 10, 10);
+	let pixels: Vec<Rgb565> = (0..100).map(|i| Rgb565::new(i as u8 % 32, i as u8, 31 - i as u8 % 32)).collect();
+	controller.write_window(rect, pixels.iter().copied()).unwrap();
+	let mut read = [Rgb565::BLACK; 100];
+	controller.read_rect(rect, &mut read).unwrap();
+	assert_eq!(&read[..], &pixels[..]);
+}
+
+#[test]
+fn screenshot_rect_writes_bmp_and_ppm() {
+	let green = Rgb565::new(0, 0x3f, 0);
+	let mut controller = initialized();
+	let rect = Rect::new(5, 6, 3, 2);
+	controller.write_window(rect, [RED, BLUE, Rgb565::WHITE, Rgb565::BLACK, green, RED]).unwrap();
+
+	let mut bmp = Vec::new();
+	controller.screenshot_rect(rect, ScreenshotFormat::Bmp, &mut bmp).unwrap();
+	let le32 = |offset: usize| u32::from_le_bytes(bmp[offset..offset + 4].try_into().unwrap());
+	assert_eq!(&bmp[..2], b"BM");
+	assert_eq!(le32(2), 54 + 2 * 12);
+	assert_eq!(le32(10), 54);
+	assert_eq!(le32(14), 40);
+	assert_eq!(le32(18), 3);
+	// Negative height: rows are stored top-down.
+	assert_eq!(le32(22) as i32, -2);
+	assert_eq!(&bmp[26..30], &[1, 0, 24, 0]);
+	assert_eq!(le32(34), 2 * 12);
+	// Three BGR pixels per row, padded to a 4-byte stride.
+	assert_eq!(&bmp[54..], &[
+		0x00, 0x00, 0xff, 0xff, 0x00, 0x00, 0xff, 0xff, 0xff, 0, 0, 0,
+		0x00, 0x00, 0x00, 0x00, 0xff, 0x00, 0x00, 0x00, 0xff, 0, 0, 0,
+	]);
+
+	let mut ppm = Vec::new();
+	controller.screenshot_rect(rect, ScreenshotFormat::Ppm, &mut ppm).unwrap();
+	let header = b"P6\n3 2\n255\n";
+	assert_eq!(&ppm[..header.len()], header);
+	assert_eq!(&ppm[header.len()..], &[
+		0xff, 0x00, 0x00, 0x00, 0x00, 0xff, 0xff, 0xff, 0xff,
+		0x00, 0x00, 0x00, 0x00, 0xff, 0x00, 0xff, 0x00, 0x00,
+	]);
+}
+
+#[test]
+fn scrolling_wraps_within_the_scroll_area() {
+	let mut controller = initialized();
+	let mut region = ScrollRegion::new(16, 288, 16).unwrap();
+	controller.set_scroll_region(&region).unwrap();
+	controller.fill_rect(Rect::new(0, 16, WIDTH, 1), RED).unwrap();
+	controller.scroll_by(&mut region, -10).unwrap();
+	assert_eq!(region.offset(), 278);
+	assert_eq!(region.logical_row(16), Some(26));
+	let (panel, _) = controller.release();
+
+	assert_eq!(panel.scroll_definition(), (16, 288, 16));
+	assert_eq!(panel.scroll_start(), Some(region.start_address()));
+	assert_eq!(panel.screen(0, 26), Rgb666::from(RED));
+	assert_eq!(panel.screen(0, 16), Rgb666::BLACK);
+}
+
+#[test]
+fn terminal_scrolls_at_the_bottom() {
+	static BLOCK: [u8; 8] = [0xff; 8];
+	let font = Font::new(&BLOCK, 8, 8, '#');
+	let mut controller = initialized();
+	let mut terminal = Terminal::new(&mut controller, font, ScrollRegion::full_screen(), RED, BLUE).unwrap();
+	assert_eq!((terminal.columns(), terminal.lines()), (30, 40));
+
+	write!(terminal, "{}", "#".repeat(31)).unwrap();
+	assert_eq!(terminal.cursor(), (1, 1));
+	for _ in 0..39 {
+		terminal.print("\n").unwrap();
+	}
+	terminal.print("#").unwrap();
+	assert_eq!(terminal.cursor(), (1, 39));
+	assert_eq!(terminal.region().offset(), 8);
+	let (panel, _) = controller.release();
+
+	// The wrapped line has scrolled to the top, the last line is at the
+	// bottom and the first has scrolled off.
+	assert_eq!(panel.screen(0, 0), Rgb666::from(RED));
+	assert_eq!(panel.screen(8, 0), Rgb666::from(BLUE));
+	assert_eq!(panel.screen(0, HEIGHT - 8), Rgb666::from(RED));
+	assert_eq!(panel.screen(8, HEIGHT - 1), Rgb666::from(BLUE));
+}
+
+#[test]
+fn fill_matches_golden_frame() {
+	let mut controller = initialized();
+	controller.clear(BLUE).unwrap();
+	controller.fill_rect(Rect::new(10, 20, 30, 40), RED).unwrap();
+	let (panel, _) = controller.release();
+
+	let mut golden = Frame::new(WIDTH, HEIGHT);
+	for y in 0..HEIGHT {
+		for x in 0..WIDTH {
+			let inside = (10..40).contains(&x) && (20..60).contains(&y);
+			golden.set_pixel(x, y, match inside {
+				false => rgb888(BLUE),
+				true  => rgb888(RED),
+			});
+		}
+	}
+	let frame = panel.frame();
+	assert_eq!(frame.diff(&golden, 0), None);
+
+	golden.set_pixel(5, 7, [0, 0xff, 0]);
+	golden.set_pixel(6, 7, [0, 0xff, 0]);
+	assert_eq!(frame.diff(&golden, 0), Some(FrameDiff::Pixels { count: 2, first: (5, 7), max_delta: 0xff }));
+	assert_eq!(frame.diff(&Frame::new(320, 240), 0), Some(FrameDiff::Size { actual: (240, 320), expected: (320, 240) }));
+}
+
+#[test]
+fn landscape_rotates_the_window() {
+	let mut controller = initialized();
+	controller.memory_access_control(Orientation::Landscape.into()).unwrap();
+	assert_eq!(controller.dimensions(), (HEIGHT, WIDTH));
+	controller.fill_rect(Rect::new(300, 10, 1, 1), RED).unwrap();
+	assert_eq!(controller.fill_rect(Rect::new(0, 240, 1, 1), RED), Err(lcd_ili9341::Error::OutOfBounds));
+	let (panel, _) = controller.release();
+
+	assert_eq!(panel.pixel(300, 10), Rgb666::from(RED));
+	assert_eq!(panel.screen(WIDTH - 1 - 10, 300), Rgb666::from(RED));
+	assert_eq!(panel.frame().pixel(WIDTH - 1 - 10, 300), rgb888(RED));
+}
+
+#[test]
+fn frame_round_trips_through_ppm() {
+	let mut controller = initialized();
+	controller.fill_rect(Rect::new(0, 0, 5, 5), RED).unwrap();
+	let frame = controller.release().0.frame();
+
+	let mut ppm = Vec::new();
+	frame.write_ppm(&mut ppm).unwrap();
+	assert_eq!(Frame::read_ppm(&mut &ppm[..]).unwrap(), frame);
+
+	let truncated = b"P6\n60000 60000\n255\n\x00\x00\x00";
+	let error = Frame::read_ppm(&mut &truncated[..]).unwrap_err();
+	assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn png_holds_the_frame_scanlines() {
+	let mut frame = Frame::new(3, 2);
+	frame.set_pixel(2, 1, [1, 2, 3]);
+	let mut png = Vec::new();
+	frame.write_png(&mut png).unwrap();
+
+	assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+	assert_eq!(&png[12..16], b"IHDR");
+	assert_eq!(&png[16..24], &[0, 0, 0, 3, 0, 0, 0, 2]);
+	// A single final stored deflate block after the zlib header.
+	let idat = &png[33..];
+	assert_eq!(&idat[4..8], b"IDAT");
+	let scanlines = [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 2, 3];
+	assert_eq!(&idat[8..15], &[0x78, 0x01, 0x01, 20, 0, !20, 0xff]);
+	assert_eq!(&idat[15..35], &scanlines[..]);
+	// Adler-32 of the scanlines.
+	assert_eq!(&idat[35..39], &[0x00, 0x1e, 0x00, 0x07]);
+	assert!(png.ends_with(b"IEND\xae\x42\x60\x82"));
+}
+
+#[test]
+fn recording_serves_scripted_memory_reads_in_chunks() {
+	let pixels: Vec<u32> = (0..100).map(|i| i << 18).collect();
+	let script = [
+		Transaction::command(0x2a, &[0, 0, 0, 9]),
+		Transaction::command(0x2b, &[0, 0, 0, 9]),
+		Transaction::command(0x2e, &[]),
+		Transaction::read_memory(&pixels),
+		Transaction::read_parameters(0x0c, &[0x55]),
+	];
+	let mut controller = Controller::new(RecordingInterface::scripted(script.clone()));
+	controller.set_window(Rect::new(0, 0, 10, 10)).unwrap();
+	controller.memory_read_start().unwrap();
+	let mut read = [Rgb666::BLACK; 100];
+	controller.read_memory(&mut read).unwrap();
+	let format = controller.read_pixel_format().unwrap();
+	let (recording, _) = controller.release();
+	recording.done();
+
+	assert_eq!(read[99], Rgb666::new(99, 0, 0));
+	assert_eq!(format.dbi(), Some(BitsPerPixel::Sixteen));
+	assert_eq!(recording.transactions(), &script[..]);
+}
+
+#[test]
+#[should_panic(expected = "expected CASET sc=0 ec=239, got CASET sc=0 ec=9")]
+fn recording_reports_mismatches_decoded() {
+	let mut controller = Controller::new(RecordingInterface::scripted([Transaction::command(0x2a, &[0, 0, 0, 239])]));
+	controller.column_address_set(0, 9).unwrap();
+}
+
+#[test]
+#[should_panic(expected = "expected PWCTRL1 gvdd=4500mV, got PWCTRL1 [00]")]
+fn recording_reports_reserved_values_in_mismatches() {
+	let script = ScriptEncoder::new().command(0xc0, &[0x00]).finish();
+	let mut controller = Controller::new(RecordingInterface::scripted([Transaction::command(0xc0, &[0x21])]));
+	controller.run_script(&script, &mut NoDelay).unwrap();
+}
+
+#[test]
+fn script_decodes_and_replays() {
+	let pixels = [RED, BLUE, RED];
+	let script = ScriptEncoder::new()
+		.command(0x01, &[])
+		.delay_ms(120)
+		.command(0xcf, &[0x00, 0xc1, 0x30])
+		.command(0xb6, &[0x08, 0x82, 0x27])
+		.command(0x3a, &[0x55])
+		.command(0x36, &[0x48])
+		.command(0x2a, &[0, 0, 0, 2])
+		.command(0x2b, &[0, 0, 0, 0])
+		.memory_write(&pixels)
+		.command(0x29, &[])
+		.finish();
+
+	let mut recorder = Controller::new(RecordingInterface::new());
+	recorder.run_script(&script, &mut NoDelay).unwrap();
+	let decoded: Vec<Command> = decode_stream(bus_bytes(recorder.release().0.transactions())).collect();
+	assert_eq!(decoded[1], Command::PowerControlB([0x00, 0xc1, 0x30]));
+	assert_eq!(decoded[2].to_string(), "DISCTRL [08, 82, 27]");
+	assert_eq!(decoded[7], Command::MemoryWriteStart);
+	assert_eq!(decoded[8], Command::Data { count: 6 });
+	assert_eq!(decoded.len(), 10);
+	assert_eq!(decoded[9].to_string(), "DISPON");
+
+	let mut controller = Controller::new(SimulatedPanel::new());
+	controller.run_script(&script, &mut NoDelay).unwrap();
+	let panel = controller.release().0;
+	assert!(panel.display_on());
+	assert_eq!(panel.bits_per_pixel(), 16);
+	for (x, &color) in pixels.iter().enumerate() {
+		assert_eq!(panel.pixel(x as u16, 0), Rgb666::from(color));
+	}
+
+	let mut corrupt = script.clone();
+	corrupt[6] = 2;
+	let mut controller = Controller::new(RecordingInterface::new());
+	assert_eq!(controller.run_script(&corrupt, &mut NoDelay), Err(lcd_ili9341::Error::InvalidScript { offset: 5 }));
+	assert!(controller.release().0.transactions().is_empty());
+}
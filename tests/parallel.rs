@@ -0,0 +1,164 @@
+//! ParallelInterface against fake 8- and 16-bit buses that log every
+//! strobe.
+
+use std::collections::VecDeque;
+use std::convert::Infallible;
+
+use lcd_ili9341::parallel::{BusWidth, ParallelBus, ParallelInterface};
+use lcd_ili9341::Interface;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Strobe {
+	/// WRX with D/CX high for data, and the value on the data port.
+	Write(bool, u16),
+	/// RDX with D/CX high for data.
+	Read(bool),
+}
+
+#[derive(Default)]
+struct Bus<const SIXTEEN: bool> {
+	dc: bool,
+	strobes: Vec<Strobe>,
+	replies: VecDeque<u16>,
+}
+
+impl<const SIXTEEN: bool> Bus<SIXTEEN> {
+	fn replying(replies: &[u16]) -> Bus<SIXTEEN> {
+		Bus {
+			replies: replies.iter().copied().collect(),
+			..Bus::default()
+		}
+	}
+}
+
+macro_rules! parallel_bus {
+	($sixteen:literal, $width:expr) => {
+		impl ParallelBus for Bus<$sixteen> {
+			type Error = Infallible;
+
+			const WIDTH: BusWidth = $width;
+
+			fn set_data_command(&mut self, data: bool) -> Result<(), Infallible> {
+				self.dc = data;
+				Ok(())
+			}
+
+			fn write_strobe(&mut self, value: u16) -> Result<(), Infallible> {
+				self.strobes.push(Strobe::Write(self.dc, value));
+				Ok(())
+			}
+
+			fn read_strobe(&mut self) -> Result<u16, Infallible> {
+				self.strobes.push(Strobe::Read(self.dc));
+				Ok(self.replies.pop_front().expect("no reply queued"))
+			}
+		}
+	};
+}
+
+parallel_bus!(false, BusWidth::Eight);
+parallel_bus!(true, BusWidth::Sixteen);
+
+fn data(values: &[u16]) -> Vec<Strobe> {
+	values.iter().map(|&value| Strobe::Write(true, value)).collect()
+}
+
+/// RGB666 (0x3f, 0x01, 0x20), sent as FCh 04h 80h at 18 bits per pixel.
+const PIXEL_18: u32 = 0x3f060;
+/// RGB666 (0x00, 0x3f, 0x3f), sent as 00h FCh FCh.
+const CYAN_18: u32 = 0x00fff;
+
+#[test]
+fn eight_bit_bus_sends_a_byte_per_strobe() {
+	let mut iface = ParallelInterface::new(Bus::<false>::default());
+	iface.write_parameters(0x2a, &[0x00, 0x09]).unwrap();
+	iface.write_memory([PIXEL_18]).unwrap();
+	iface.write_parameters(0x3a, &[0x55]).unwrap();
+	iface.write_memory([0xf81f]).unwrap();
+	let bus = iface.release();
+
+	let mut expected = vec![Strobe::Write(false, 0x2a)];
+	expected.extend(data(&[0x00, 0x09, 0xfc, 0x04, 0x80]));
+	expected.push(Strobe::Write(false, 0x3a));
+	expected.extend(data(&[0x55, 0xf8, 0x1f]));
+	assert_eq!(bus.strobes, expected);
+}
+
+#[test]
+fn sixteen_bit_bus_pairs_bytes_and_pads_the_odd_one() {
+	let mut iface = ParallelInterface::new(Bus::<true>::default());
+	// Parameters stay on DB[7:0] whatever the bus width.
+	iface.write_parameters(0x2a, &[0x00, 0x09]).unwrap();
+	iface.write_memory([PIXEL_18, CYAN_18]).unwrap();
+	iface.write_memory([PIXEL_18]).unwrap();
+	iface.write_parameters(0x3a, &[0x55]).unwrap();
+	iface.write_memory([0xf81f, 0x07e0]).unwrap();
+	let bus = iface.release();
+
+	let mut expected = vec![Strobe::Write(false, 0x2a)];
+	expected.extend(data(&[0x00, 0x09]));
+	expected.extend(data(&[0xfc04, 0x8000, 0xfcfc]));
+	expected.extend(data(&[0xfc04, 0x8000]));
+	expected.push(Strobe::Write(false, 0x3a));
+	expected.extend(data(&[0x55, 0xf81f, 0x07e0]));
+	assert_eq!(bus.strobes, expected);
+}
+
+#[test]
+fn parameter_reads_start_with_a_dummy_cycle() {
+	let mut iface = ParallelInterface::new(Bus::<false>::replying(&[0xff, 0x00, 0x93, 0x41]));
+	let mut id = [0u8; 3];
+	iface.read_parameters(0x04, &mut id).unwrap();
+	let bus = iface.release();
+
+	assert_eq!(id, [0x00, 0x93, 0x41]);
+	assert_eq!(bus.strobes, [
+		Strobe::Write(false, 0x04),
+		Strobe::Read(true),
+		Strobe::Read(true),
+		Strobe::Read(true),
+		Strobe::Read(true),
+	]);
+}
+
+#[test]
+fn sixteen_bit_memory_reads_carry_a_byte_across_calls() {
+	// A dummy word, then R0 G0 | B0 R1 | G1 B1, then after Read Memory
+	// Continue another dummy word and R2 G2 | B2 xx.
+	let replies = [0xffff, 0x1020, 0x3040, 0x5060, 0xffff, 0x7080, 0x90a0];
+	let mut iface = ParallelInterface::new(Bus::<true>::replying(&replies));
+	iface.write_parameters(0x2e, &[]).unwrap();
+	let mut first = [0u32; 1];
+	iface.read_memory(&mut first).unwrap();
+	let mut second = [0u32; 1];
+	iface.read_memory(&mut second).unwrap();
+	// A new command drops the carried byte and expects a dummy word.
+	iface.write_parameters(0x3e, &[]).unwrap();
+	let mut third = [0u32; 1];
+	iface.read_memory(&mut third).unwrap();
+	let bus = iface.release();
+
+	assert_eq!((first[0], second[0], third[0]), (0x102030, 0x405060, 0x708090));
+	assert_eq!(bus.strobes, [
+		Strobe::Write(false, 0x2e),
+		Strobe::Read(true),
+		Strobe::Read(true),
+		Strobe::Read(true),
+		Strobe::Read(true),
+		Strobe::Write(false, 0x3e),
+		Strobe::Read(true),
+		Strobe::Read(true),
+		Strobe::Read(true),
+	]);
+}
+
+#[test]
+fn panel_reset_restores_18_bits_per_pixel() {
+	let mut iface = ParallelInterface::new(Bus::<false>::default());
+	iface.write_parameters(0x3a, &[0x55]).unwrap();
+	iface.panel_reset().unwrap();
+	iface.write_memory([PIXEL_18]).unwrap();
+	let bus = iface.release();
+
+	assert!(bus.strobes.ends_with(&data(&[0xfc, 0x04, 0x80])));
+}
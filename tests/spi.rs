@@ -0,0 +1,264 @@
+//! SpiInterface and AsyncSpiInterface against a fake SPI device that logs
+//! every transaction with the D/CX level it was made at.
+
+#![cfg(feature = "embedded-hal")]
+
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::convert::Infallible;
+use std::rc::Rc;
+
+use embedded_hal::spi::Operation;
+use lcd_ili9341::spi::{SpiError, SpiInterface};
+use lcd_ili9341::{Controller, Interface};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Op {
+	Write(Vec<u8>),
+	Read(usize),
+}
+
+/// One chip-select window: D/CX high for data, and its operations.
+type Transfer = (bool, Vec<Op>);
+
+#[derive(Clone, Default)]
+struct Bus {
+	dc: Rc<Cell<bool>>,
+	log: Rc<RefCell<Vec<Transfer>>>,
+	replies: Rc<RefCell<VecDeque<u8>>>,
+}
+
+impl Bus {
+	fn reply(&self, bytes: &[u8]) {
+		self.replies.borrow_mut().extend(bytes);
+	}
+
+	fn take(&self) -> Vec<Transfer> {
+		self.log.borrow_mut().drain(..).collect()
+	}
+
+	fn transaction(&self, operations: &mut [Operation<'_, u8>]) {
+		let mut ops = Vec::new();
+		for operation in operations {
+			match operation {
+				Operation::Write(bytes) => ops.push(Op::Write(bytes.to_vec())),
+				Operation::Read(bytes) => {
+					for byte in bytes.iter_mut() {
+						*byte = self.replies.borrow_mut().pop_front().expect("no reply queued");
+					}
+					ops.push(Op::Read(bytes.len()));
+				},
+				_ => panic!("unexpected operation"),
+			}
+		}
+		self.log.borrow_mut().push((self.dc.get(), ops));
+	}
+}
+
+struct Spi(Bus);
+
+impl embedded_hal::spi::ErrorType for Spi {
+	type Error = Infallible;
+}
+
+impl embedded_hal::spi::SpiDevice for Spi {
+	fn transaction(&mut self, operations: &mut [Operation<'_, u8>]) -> Result<(), Infallible> {
+		self.0.transaction(operations);
+		Ok(())
+	}
+}
+
+#[cfg(feature = "async")]
+impl embedded_hal_async::spi::SpiDevice for Spi {
+	async fn transaction(&mut self, operations: &mut [Operation<'_, u8>]) -> Result<(), Infallible> {
+		self.0.transaction(operations);
+		Ok(())
+	}
+}
+
+struct Dc(Bus);
+
+impl embedded_hal::digital::ErrorType for Dc {
+	type Error = Infallible;
+}
+
+impl embedded_hal::digital::OutputPin for Dc {
+	fn set_low(&mut self) -> Result<(), Infallible> {
+		self.0.dc.set(false);
+		Ok(())
+	}
+
+	fn set_high(&mut self) -> Result<(), Infallible> {
+		self.0.dc.set(true);
+		Ok(())
+	}
+}
+
+fn command(bytes: &[u8]) -> Transfer {
+	(false, vec![Op::Write(bytes.to_vec())])
+}
+
+fn data(bytes: &[u8]) -> Transfer {
+	(true, vec![Op::Write(bytes.to_vec())])
+}
+
+fn read(command: u8, counts: &[usize]) -> Transfer {
+	let mut ops = vec![Op::Write(vec![command])];
+	ops.extend(counts.iter().map(|&count| Op::Read(count)));
+	(false, ops)
+}
+
+/// Bytes of an RDDID reply of `id`, arriving one dummy clock late, and
+/// the byte clocked after them.
+fn late_by_one_bit(id: [u8; 3]) -> [u8; 4] {
+	[id[0] >> 1, id[0] << 7 | id[1] >> 1, id[1] << 7 | id[2] >> 1, id[2] << 7]
+}
+
+/// Pixels with a recognizable byte pattern, as read back from GRAM.
+fn gram_bytes(count: usize) -> Vec<u8> {
+	(0..count * 3).map(|i| i as u8).collect()
+}
+
+fn gram_words(count: usize) -> Vec<u32> {
+	(0..count as u32).map(|i| (i * 3) << 16 | (i * 3 + 1) << 8 | (i * 3 + 2)).collect()
+}
+
+/// Run every kind of transfer through `iface`: parameters, both pixel
+/// formats, repeated fills, register reads and a chunked memory read.
+fn exercise<I>(iface: &mut I) -> ([u8; 3], u8, Vec<u32>)
+	where I: Interface<Error = SpiError<Infallible, Infallible>>
+{
+	iface.write_parameters(0x2a, &[0x00, 0x00, 0x00, 0x09]).unwrap();
+	iface.write_memory([0x3f060]).unwrap();
+	iface.write_parameters(0x3a, &[0x55]).unwrap();
+	iface.write_memory([0xf800, 0x001f]).unwrap();
+	iface.write_repeated(0x1234, 70).unwrap();
+
+	let mut id = [0u8; 3];
+	iface.read_parameters(0x04, &mut id).unwrap();
+	let mut colmod = [0u8; 1];
+	iface.read_parameters(0x0c, &mut colmod).unwrap();
+
+	iface.write_parameters(0x2e, &[]).unwrap();
+	let mut words = vec![0u32; 40];
+	iface.read_memory(&mut words).unwrap();
+	(id, colmod[0], words)
+}
+
+fn queue_replies(bus: &Bus) {
+	bus.reply(&late_by_one_bit([0x12, 0x34, 0x57]));
+	bus.reply(&[0x55]);
+	bus.reply(&[0xff]);
+	bus.reply(&gram_bytes(32));
+	bus.reply(&[0xff]);
+	bus.reply(&gram_bytes(40)[96..]);
+}
+
+fn expected_transfers() -> Vec<Transfer> {
+	let mut transfers = vec![
+		command(&[0x2a]),
+		data(&[0x00, 0x00, 0x00, 0x09]),
+		data(&[0xfc, 0x04, 0x80]),
+		command(&[0x3a]),
+		data(&[0x55]),
+		data(&[0xf8, 0x00, 0x00, 0x1f]),
+	];
+	let fill: Vec<u8> = [0x12, 0x34].repeat(32);
+	transfers.push(data(&fill));
+	transfers.push(data(&fill));
+	transfers.push(data(&fill[..12]));
+	transfers.push(read(0x04, &[3, 1]));
+	transfers.push(read(0x0c, &[1]));
+	// Memory Read is held back until the read, then continued in a
+	// second transaction past 32 pixels.
+	transfers.push(read(0x2e, &[1, 96]));
+	transfers.push(read(0x3e, &[1, 24]));
+	transfers
+}
+
+#[test]
+fn wire_bytes_and_decoded_reads() {
+	let bus = Bus::default();
+	queue_replies(&bus);
+	let mut iface = SpiInterface::new(Spi(bus.clone()), Dc(bus.clone()));
+	let (id, colmod, words) = exercise(&mut iface);
+
+	assert_eq!(bus.take(), expected_transfers());
+	assert_eq!(id, [0x12, 0x34, 0x57]);
+	assert_eq!(colmod, 0x55);
+	assert_eq!(words, gram_words(40));
+	assert!(bus.replies.borrow().is_empty());
+}
+
+#[test]
+fn controller_decodes_display_identification() {
+	let bus = Bus::default();
+	bus.reply(&late_by_one_bit([0x00, 0x93, 0x41]));
+	let mut controller = Controller::new(SpiInterface::new(Spi(bus.clone()), Dc(bus.clone())));
+	let id = controller.read_display_identification().unwrap();
+
+	assert_eq!((id.manufacturer_id(), id.version_id(), id.driver_id()), (0x00, 0x93, 0x41));
+	assert_eq!(bus.take(), [read(0x04, &[3, 1])]);
+}
+
+#[cfg(feature = "async")]
+mod asynch {
+	use std::future::Future;
+	use std::pin::pin;
+	use std::sync::Arc;
+	use std::task::{Context, Poll, Wake, Waker};
+
+	use lcd_ili9341::asynch::AsyncInterface;
+	use lcd_ili9341::spi::AsyncSpiInterface;
+
+	use super::*;
+
+	struct NoWake;
+
+	impl Wake for NoWake {
+		fn wake(self: Arc<Self>) {}
+	}
+
+	/// Poll `future` to completion. The fake device never pends.
+	fn block_on<F: Future>(future: F) -> F::Output {
+		let waker = Waker::from(Arc::new(NoWake));
+		let mut future = pin!(future);
+		match future.as_mut().poll(&mut Context::from_waker(&waker)) {
+			Poll::Ready(output) => output,
+			Poll::Pending => panic!("fake SPI device pended"),
+		}
+	}
+
+	async fn exercise<I>(iface: &mut I) -> ([u8; 3], u8, Vec<u32>)
+		where I: AsyncInterface<Error = SpiError<Infallible, Infallible>>
+	{
+		iface.write_parameters(0x2a, &[0x00, 0x00, 0x00, 0x09]).await.unwrap();
+		iface.write_memory([0x3f060]).await.unwrap();
+		iface.write_parameters(0x3a, &[0x55]).await.unwrap();
+		iface.write_memory([0xf800, 0x001f]).await.unwrap();
+		iface.write_repeated(0x1234, 70).await.unwrap();
+
+		let mut id = [0u8; 3];
+		iface.read_parameters(0x04, &mut id).await.unwrap();
+		let mut colmod = [0u8; 1];
+		iface.read_parameters(0x0c, &mut colmod).await.unwrap();
+
+		iface.write_parameters(0x2e, &[]).await.unwrap();
+		let mut words = vec![0u32; 40];
+		iface.read_memory(&mut words).await.unwrap();
+		(id, colmod[0], words)
+	}
+
+	#[test]
+	fn async_wire_bytes_match_sync() {
+		let bus = Bus::default();
+		queue_replies(&bus);
+		let mut iface = AsyncSpiInterface::new(Spi(bus.clone()), Dc(bus.clone()));
+		let (id, colmod, words) = block_on(exercise(&mut iface));
+
+		assert_eq!(bus.take(), expected_transfers());
+		assert_eq!(id, [0x12, 0x34, 0x57]);
+		assert_eq!(colmod, 0x55);
+		assert_eq!(words, gram_words(40));
+	}
+}
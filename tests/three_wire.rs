@@ -0,0 +1,228 @@
+//! ThreeWireInterface against a fake SerialBus logging every frame, and
+//! BitBangBus against fake pins sampling SDI on each rising SCL edge.
+
+use std::collections::VecDeque;
+use std::convert::Infallible;
+
+use lcd_ili9341::three_wire::{SerialBus, ThreeWireInterface};
+use lcd_ili9341::Interface;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Event {
+	Select(bool),
+	Write(u16, u8),
+	Read(u8),
+}
+
+#[derive(Default)]
+struct Bus {
+	events: Vec<Event>,
+	replies: VecDeque<u16>,
+}
+
+impl Bus {
+	fn replying(replies: &[u16]) -> Bus {
+		Bus {
+			events: Vec::new(),
+			replies: replies.iter().copied().collect(),
+		}
+	}
+}
+
+impl SerialBus for Bus {
+	type Error = Infallible;
+
+	fn select(&mut self, selected: bool) -> Result<(), Infallible> {
+		self.events.push(Event::Select(selected));
+		Ok(())
+	}
+
+	fn write_bits(&mut self, value: u16, bits: u8) -> Result<(), Infallible> {
+		self.events.push(Event::Write(value, bits));
+		Ok(())
+	}
+
+	fn read_bits(&mut self, bits: u8) -> Result<u16, Infallible> {
+		self.events.push(Event::Read(bits));
+		Ok(self.replies.pop_front().expect("no reply queued"))
+	}
+}
+
+#[test]
+fn bytes_travel_in_nine_bit_frames() {
+	let mut iface = ThreeWireInterface::new(Bus::default());
+	iface.write_parameters(0x2a, &[0x00, 0x09]).unwrap();
+	iface.write_parameters(0x3a, &[0x55]).unwrap();
+	iface.write_memory([0xf81f]).unwrap();
+	let bus = iface.release();
+
+	assert_eq!(bus.events, [
+		Event::Select(true),
+		Event::Write(0x02a, 9),
+		Event::Write(0x100, 9),
+		Event::Write(0x109, 9),
+		Event::Select(false),
+		Event::Select(true),
+		Event::Write(0x03a, 9),
+		Event::Write(0x155, 9),
+		Event::Select(false),
+		Event::Select(true),
+		Event::Write(0x1f8, 9),
+		Event::Write(0x11f, 9),
+		Event::Select(false),
+	]);
+}
+
+#[test]
+fn multi_byte_register_reads_skip_a_dummy_clock() {
+	let mut iface = ThreeWireInterface::new(Bus::replying(&[0, 0x00, 0x93, 0x41, 0x55]));
+	let mut id = [0u8; 3];
+	iface.read_parameters(0x04, &mut id).unwrap();
+	let mut colmod = [0u8; 1];
+	iface.read_parameters(0x0c, &mut colmod).unwrap();
+	let bus = iface.release();
+
+	assert_eq!((id, colmod), ([0x00, 0x93, 0x41], [0x55]));
+	assert_eq!(bus.events, [
+		Event::Select(true),
+		Event::Write(0x004, 9),
+		Event::Read(1),
+		Event::Read(8),
+		Event::Read(8),
+		Event::Read(8),
+		Event::Select(false),
+		Event::Select(true),
+		Event::Write(0x00c, 9),
+		Event::Read(8),
+		Event::Select(false),
+	]);
+}
+
+#[test]
+fn memory_reads_follow_their_command_in_one_window() {
+	let mut iface = ThreeWireInterface::new(Bus::replying(&[0xff, 0x10, 0x2030, 0x40, 0x5060]));
+	iface.write_parameters(0x2e, &[]).unwrap();
+	let mut pixels = [0u32; 2];
+	iface.read_memory(&mut pixels).unwrap();
+	let bus = iface.release();
+
+	assert_eq!(pixels, [0x102030, 0x405060]);
+	assert_eq!(bus.events, [
+		Event::Select(true),
+		Event::Write(0x02e, 9),
+		Event::Read(8),
+		Event::Read(8),
+		Event::Read(16),
+		Event::Read(8),
+		Event::Read(16),
+		Event::Select(false),
+	]);
+}
+
+#[cfg(feature = "embedded-hal")]
+mod bitbang {
+	use std::cell::RefCell;
+	use std::rc::Rc;
+
+	use embedded_hal::digital::{ErrorType, InputPin, OutputPin};
+	use lcd_ili9341::three_wire::BitBangBus;
+
+	use super::*;
+
+	#[derive(Default)]
+	struct Wire {
+		selected: bool,
+		sdi: bool,
+		/// SDI level at each rising SCL edge while selected.
+		sampled: Vec<bool>,
+		/// SDO levels to present at successive rising edges.
+		replies: VecDeque<bool>,
+		sdo: bool,
+	}
+
+	#[derive(Copy, Clone)]
+	enum Line {
+		Cs,
+		Scl,
+		Sdi,
+		Sdo,
+	}
+
+	struct Pin(Rc<RefCell<Wire>>, Line);
+
+	impl ErrorType for Pin {
+		type Error = Infallible;
+	}
+
+	impl OutputPin for Pin {
+		fn set_low(&mut self) -> Result<(), Infallible> {
+			let mut wire = self.0.borrow_mut();
+			match self.1 {
+				Line::Cs => wire.selected = true,
+				Line::Sdi => wire.sdi = false,
+				_ => (),
+			}
+			Ok(())
+		}
+
+		fn set_high(&mut self) -> Result<(), Infallible> {
+			let mut wire = self.0.borrow_mut();
+			match self.1 {
+				Line::Cs => wire.selected = false,
+				Line::Scl => if wire.selected {
+					let sdi = wire.sdi;
+					wire.sampled.push(sdi);
+					wire.sdo = wire.replies.pop_front().unwrap_or(false);
+				},
+				Line::Sdi => wire.sdi = true,
+				Line::Sdo => (),
+			}
+			Ok(())
+		}
+	}
+
+	impl InputPin for Pin {
+		fn is_high(&mut self) -> Result<bool, Infallible> {
+			Ok(self.0.borrow().sdo)
+		}
+
+		fn is_low(&mut self) -> Result<bool, Infallible> {
+			Ok(!self.0.borrow().sdo)
+		}
+	}
+
+	fn bits(value: u32, count: u8) -> Vec<bool> {
+		(0..count).rev().map(|bit| (value >> bit) & 1 != 0).collect()
+	}
+
+	fn interface(wire: &Rc<RefCell<Wire>>) -> ThreeWireInterface<BitBangBus<Pin, Pin, Pin, Pin>> {
+		ThreeWireInterface::new(BitBangBus::new(
+			Pin(wire.clone(), Line::Cs),
+			Pin(wire.clone(), Line::Scl),
+			Pin(wire.clone(), Line::Sdi),
+			Pin(wire.clone(), Line::Sdo),
+		))
+	}
+
+	#[test]
+	fn frames_are_clocked_msb_first_with_the_dcx_bit_leading() {
+		let wire = Rc::new(RefCell::new(Wire::default()));
+		interface(&wire).write_parameters(0x3a, &[0x55]).unwrap();
+
+		let wire = wire.borrow();
+		assert!(!wire.selected);
+		assert_eq!(wire.sampled, [bits(0x03a, 9), bits(0x155, 9)].concat());
+	}
+
+	#[test]
+	fn register_reads_clock_one_dummy_bit_before_the_data() {
+		let wire = Rc::new(RefCell::new(Wire::default()));
+		// Nothing during the command frame, a set dummy bit, then ID1-ID3.
+		wire.borrow_mut().replies = [vec![false; 9], bits(0x1_009341, 25)].concat().into();
+		let mut id = [0u8; 3];
+		interface(&wire).read_parameters(0x04, &mut id).unwrap();
+
+		assert_eq!(id, [0x00, 0x93, 0x41]);
+		assert_eq!(wire.borrow().sampled.len(), 9 + 1 + 24);
+	}
+}